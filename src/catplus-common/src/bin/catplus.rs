@@ -0,0 +1,79 @@
+use anyhow::{Context, Result};
+use catplus_common::{
+    graph::skolem::deskolemize_graph,
+    rdf::{
+        rdf_diff::diff_graphs,
+        rdf_parser::{parse_to_graph, RdfSyntax},
+    },
+};
+use clap::{Parser, Subcommand};
+use sophia::inmem::graph::LightGraph;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+// Tools to inspect cat+ RDF outputs
+#[derive(Parser, Debug)]
+struct Args {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Compare two RDF files and print the added and removed triples,
+    /// grouped by subject type. Exits with 1 if the graphs differ.
+    Diff {
+        /// Path to the old RDF file.
+        old: PathBuf,
+
+        /// Path to the new RDF file.
+        new: PathBuf,
+
+        /// RDF syntax of the inputs.
+        /// Guessed from the file extension or the content if not provided.
+        #[arg(long, value_enum)]
+        input_format: Option<RdfSyntax>,
+
+        /// Turn the skolem IRIs of materialized outputs back into blank nodes before comparing,
+        /// e.g. to compare an output of `--materialize` with one without.
+        #[arg(long, default_value_t = false)]
+        deskolemize: bool,
+    },
+}
+
+fn read_graph(
+    path: &Path,
+    input_format: Option<RdfSyntax>,
+    deskolemize: bool,
+) -> Result<LightGraph> {
+    let content =
+        fs::read_to_string(path).with_context(|| format!("Failed to read '{}'", path.display()))?;
+    let graph = parse_to_graph(&content, input_format.or_else(|| RdfSyntax::from_path(path)))
+        .with_context(|| format!("Failed to parse '{}'", path.display()))?;
+    if !deskolemize {
+        return Ok(graph);
+    }
+    deskolemize_graph(&graph).with_context(|| format!("Failed to deskolemize '{}'", path.display()))
+}
+
+fn main() -> Result<ExitCode> {
+    let args = Args::parse();
+
+    match args.command {
+        Command::Diff { old, new, input_format, deskolemize } => {
+            let diff = diff_graphs(
+                &read_graph(&old, input_format, deskolemize)?,
+                &read_graph(&new, input_format, deskolemize)?,
+            )?;
+            println!("{}", diff);
+            if diff.is_empty() {
+                Ok(ExitCode::SUCCESS)
+            } else {
+                Ok(ExitCode::FAILURE)
+            }
+        }
+    }
+}
@@ -1,16 +1,38 @@
 use crate::{
-    graph::namespaces::{allores, cat, schema},
-    rdf::rdf_serializers::{serialize_graph_to_jsonld, serialize_graph_to_turtle},
+    graph::namespaces::{allores, cat, prov, schema},
+    rdf::rdf_serializers::{
+        serialize_graph_to_canonical_nquads, serialize_graph_to_compact_jsonld,
+        serialize_graph_to_jsonld, serialize_graph_to_ntriples, serialize_graph_to_rdfxml,
+        serialize_graph_to_turtle, serialize_graphs_to_nquads, serialize_graphs_to_trig,
+    },
 };
 use anyhow::{Context, Result};
-use sophia::inmem::graph::LightGraph;
+use sophia::{api::ns::xsd, inmem::graph::LightGraph};
 use sophia_api::{prelude::*, term::SimpleTerm};
 
-use super::insert_into::InsertIntoGraph;
+use super::{
+    insert_into::InsertIntoGraph,
+    iri_base::IriBase,
+    provenance::{Provenance, ProvenanceGraph},
+    references::{find_dangling_references, DanglingReference},
+    skolem::{deskolemize_graph, skolemize_graph},
+    utils::{relabel_blank_nodes, sha256_hex},
+};
 
 /// An RDF Graph
 pub struct GraphBuilder {
     pub graph: LightGraph,
+    /// The content URL added by [GraphBuilder::link_content], if any.
+    pub content_url: Option<String>,
+    /// Keep random blank node identifiers instead of relabeling them deterministically.
+    pub random_bnodes: bool,
+    /// Provenance triples kept apart from the data by [GraphBuilder::add_provenance], if any.
+    pub provenance_graph: Option<LightGraph>,
+    /// Name of the provenance named graph, instead of the one derived from the content URL.
+    /// Graphs merged from several files have no content URL of their own.
+    pub provenance_graph_name: Option<String>,
+    /// Base IRIs of the graph, set by [GraphBuilder::rebase].
+    pub iri_base: IriBase,
 }
 
 /// Builds an RDF graph of Synthesis data for the cat+ ontology.
@@ -22,18 +44,33 @@ pub struct GraphBuilder {
 /// * serialize_to_turtle: serializes the graph to a turtle output
 impl GraphBuilder {
     pub fn new() -> Self {
-        Self { graph: LightGraph::new() }
+        Self {
+            graph: LightGraph::new(),
+            content_url: None,
+            random_bnodes: false,
+            provenance_graph: None,
+            provenance_graph_name: None,
+            iri_base: IriBase::default(),
+        }
     }
 
     /// Inserts a new object into the graph as a collection of triples.
+    ///
+    /// Blank nodes are then relabeled with [relabel_blank_nodes], unless `random_bnodes` is set.
     pub fn insert(&mut self, other: &dyn InsertIntoGraph) -> Result<()> {
         other.insert_into(&mut self.graph, other.get_uri())?;
+        if !self.random_bnodes {
+            self.graph = relabel_blank_nodes(&self.graph)?;
+        }
 
         Ok(())
     }
 
     /// Adds a content URL to the graph.
+    ///
+    /// The URL is also kept as the name of the graph for quad serializations.
     pub fn link_content(&mut self, content_url: &str) -> Result<()> {
+        self.content_url = Some(content_url.to_string());
         let campaign = &cat::Campaign.as_simple();
         let liquid_chromatography_document = &allores::AFR_0002524.as_simple();
 
@@ -45,7 +82,7 @@ impl GraphBuilder {
 
         // exit with warning if no triples are found.
         if triples.is_empty() {
-            println!("Warning: No triples found for contentUrl insertion.");
+            eprintln!("Warning: No triples found for contentUrl insertion.");
             return Ok(());
         } else if triples.len() > 1 {
             return Err(anyhow::anyhow!("Multiple triples found for contentUrl insertion"));
@@ -86,43 +123,144 @@ impl GraphBuilder {
         Ok(())
     }
 
-    /// Materializes blank nodes in the graph by replacing them with URIs.
-    /// If a prefix is given, it will be used for all materialized blank nodes.
-    /// Otherwise, the empty string is used as the prefix.
-    pub fn materialize_blank_nodes(&mut self, prefix: Option<&str>) -> Result<()> {
-        let mut materialized_graph = LightGraph::new();
+    /// Merges the triples of `other` into the graph.
+    ///
+    /// Resources with the same IRI are unified, such as the batches, products and
+    /// chemicals identified by [generate_resource_identifier_uri](super::utils::generate_resource_identifier_uri).
+    /// The provenance graphs are merged as well.
+    ///
+    /// Both graphs must have the same [IriBase].
+    pub fn merge(&mut self, other: &GraphBuilder) -> Result<()> {
+        if self.iri_base != other.iri_base {
+            anyhow::bail!("Cannot merge graphs with different base IRIs");
+        }
+        for triple in other.graph.triples() {
+            let [subject, predicate, object] = triple?;
+            self.graph.insert(subject, predicate, object)?;
+        }
+        if let Some(other_provenance) = &other.provenance_graph {
+            let provenance_graph = self.provenance_graph.get_or_insert_with(LightGraph::new);
+            for triple in other_provenance.triples() {
+                let [subject, predicate, object] = triple?;
+                provenance_graph.insert(subject, predicate, object)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds PROV-O triples describing how the graph was produced.
+    ///
+    /// The conversion activity is linked with `prov:wasGeneratedBy` to the document node
+    /// found by [GraphBuilder::link_content], or to the content URL itself if there is none.
+    /// With [ProvenanceGraph::Named], the triples are kept in [GraphBuilder::provenance_graph].
+    pub fn add_provenance(
+        &mut self,
+        provenance: &Provenance,
+        provenance_graph: ProvenanceGraph,
+    ) -> Result<()> {
+        let document = self.document_node()?;
+        let mut provenance_triples = LightGraph::new();
+        provenance.insert_into(&mut provenance_triples, provenance.get_uri())?;
+        if !self.random_bnodes {
+            provenance_triples = relabel_blank_nodes(&provenance_triples)?;
+        }
 
-        for triple in self.graph.triples_matching(Any, Any, Any) {
+        let graph = match provenance_graph {
+            ProvenanceGraph::Default => &mut self.graph,
+            ProvenanceGraph::Named => self.provenance_graph.get_or_insert_with(LightGraph::new),
+        };
+        graph.insert(
+            &document,
+            prov::generatedAtTime.as_simple(),
+            (provenance.ended_at.as_str() * xsd::dateTime).as_simple(),
+        )?;
+        graph.insert(&document, prov::wasGeneratedBy.as_simple(), provenance.get_uri())?;
+        for triple in provenance_triples.triples() {
             let [subject, predicate, object] = triple?;
+            graph.insert(subject, predicate, object)?;
+        }
+        Ok(())
+    }
 
-            // If the subject is a blank node, replace it with a URI
-            let new_subject = match subject {
-                SimpleTerm::BlankNode(s) => {
-                    let new_iri = format!("{}{}", prefix.unwrap_or_default(), s.as_str());
-                    IriRef::new(new_iri.to_owned())
-                }
-                SimpleTerm::Iri(s) => IriRef::new(s.as_str().to_owned()),
-                _ => panic!("Unexpected subject type"),
-            }?;
-
-            // If the object is a blank node, replace it with a URI
-            // In any other case, we just clone it.
-            match object {
-                SimpleTerm::BlankNode(o) => {
-                    let new_o = format!("{}{}", prefix.unwrap_or_default(), o.as_str());
-                    materialized_graph.insert(
-                        new_subject,
-                        predicate.clone(),
-                        IriRef::new(new_o.as_str().to_owned()).unwrap(),
-                    )?;
-                }
-                _ => {
-                    materialized_graph.insert(new_subject, predicate.clone(), object.clone())?;
-                }
-            };
+    /// Returns the node described by the content URL, or the content URL itself.
+    fn document_node(&self) -> Result<SimpleTerm<'static>> {
+        let content_url = self
+            .content_url
+            .as_deref()
+            .context("A content URL must be linked to add provenance")?;
+        let document = self
+            .graph
+            .triples_matching(Any, [schema::contentUrl], [content_url.as_simple()])
+            .next()
+            .transpose()
+            .map_err(anyhow::Error::from)?
+            .map(|[subject, _, _]| subject.clone().into_term());
+
+        match document {
+            Some(document) => Ok(document),
+            None => Ok(IriRef::new(content_url.to_owned())
+                .with_context(|| format!("Invalid content URL '{}'", content_url))?
+                .into_term()),
+        }
+    }
+
+    /// Name of the provenance named graph, given or derived from the content URL.
+    fn provenance_graph_iri(&self) -> Option<String> {
+        self.provenance_graph_name.clone().or_else(|| {
+            self.content_url.as_ref().map(|content_url| format!("{}#provenance", content_url))
+        })
+    }
+
+    /// The data graph and the provenance graph, if any, with their names.
+    fn named_graphs(&self) -> Vec<(&LightGraph, Option<String>)> {
+        let mut graphs = vec![(&self.graph, self.content_url.clone())];
+        if let Some(provenance_graph) = &self.provenance_graph {
+            graphs.push((provenance_graph, self.provenance_graph_iri()));
+        }
+        graphs
+    }
+
+    /// Returns the references of the graph that do not resolve, see [find_dangling_references].
+    pub fn dangling_references(&self) -> Result<Vec<DanglingReference>> {
+        find_dangling_references(&self.graph, &self.iri_base)
+    }
+
+    /// Replaces the default resource and ontology namespaces with those of `iri_base`.
+    ///
+    /// The graph must still use the default namespaces. The provenance graph is rebased as well.
+    pub fn rebase(&mut self, iri_base: IriBase) -> Result<()> {
+        if !self.iri_base.is_default() {
+            anyhow::bail!("The graph has already been rebased");
+        }
+        self.graph = iri_base.rebase_graph(&self.graph).context("Failed to rebase graph")?;
+        if let Some(provenance_graph) = &self.provenance_graph {
+            self.provenance_graph = Some(
+                iri_base
+                    .rebase_graph(provenance_graph)
+                    .context("Failed to rebase provenance graph")?,
+            );
+        }
+        self.iri_base = iri_base;
+        Ok(())
+    }
+
+    /// Skolemizes the graph, replacing blank nodes with `<base>/.well-known/genid/<id>` IRIs.
+    ///
+    /// The provenance graph is skolemized as well.
+    pub fn skolemize(&mut self, base: &str) -> Result<()> {
+        self.graph = skolemize_graph(&self.graph, base).context("Failed to skolemize graph")?;
+        if let Some(provenance_graph) = &self.provenance_graph {
+            self.provenance_graph = Some(
+                skolemize_graph(provenance_graph, base)
+                    .context("Failed to skolemize provenance graph")?,
+            );
         }
+        Ok(())
+    }
 
-        self.graph = materialized_graph;
+    /// Replaces skolem IRIs with blank nodes, reversing [GraphBuilder::skolemize].
+    pub fn deskolemize(&mut self) -> Result<()> {
+        self.graph = deskolemize_graph(&self.graph).context("Failed to deskolemize graph")?;
         Ok(())
     }
 
@@ -134,7 +272,8 @@ impl GraphBuilder {
     /// A `Result` containing the graph as Turtle serialization, or an error
     /// if the graph retrieval fails.
     pub fn serialize_to_turtle(&self) -> Result<String> {
-        serialize_graph_to_turtle(&self.graph).context("Failed to serialize graph to Turtle")
+        serialize_graph_to_turtle(&self.graph, &self.iri_base.prefix_map())
+            .context("Failed to serialize graph to Turtle")
     }
 
     /// Get the turtle serialization of the RDF graph
@@ -147,4 +286,95 @@ impl GraphBuilder {
     pub fn serialize_to_jsonld(&self) -> Result<String> {
         serialize_graph_to_jsonld(&self.graph).context("Failed to serialize graph to JSON-LD")
     }
+
+    /// Get the compacted JSON-LD serialization of the RDF graph
+    ///
+    /// # Arguments
+    /// - `context_url`: URL of the `@context` to reference, or `None` to embed it inline.
+    /// - `frame_type`: IRI of the type of the root nodes to frame the graph on, if any.
+    ///
+    /// # Returns
+    /// A `Result` containing the graph as compacted JSON-LD serialization, or an error
+    /// if the graph retrieval fails.
+    pub fn serialize_to_compact_jsonld(
+        &self,
+        context_url: Option<&str>,
+        frame_type: Option<&str>,
+    ) -> Result<String> {
+        serialize_graph_to_compact_jsonld(
+            &self.graph,
+            &self.iri_base.prefix_map(),
+            context_url,
+            frame_type,
+        )
+        .context("Failed to serialize graph to compacted JSON-LD")
+    }
+
+    /// Get the N-Triples serialization of the RDF graph
+    ///
+    /// # Returns
+    /// A `Result` containing the graph as N-Triples serialization, or an error
+    /// if the graph retrieval fails.
+    pub fn serialize_to_ntriples(&self) -> Result<String> {
+        serialize_graph_to_ntriples(&self.graph).context("Failed to serialize graph to N-Triples")
+    }
+
+    /// Get the N-Quads serialization of the RDF graph
+    ///
+    /// The triples are placed in a named graph identified by the content URL,
+    /// or in the default graph if no content URL was linked. The provenance graph,
+    /// if any, is named by the content URL with a `#provenance` fragment.
+    ///
+    /// # Returns
+    /// A `Result` containing the graph as N-Quads serialization, or an error
+    /// if the graph retrieval fails.
+    pub fn serialize_to_nquads(&self) -> Result<String> {
+        serialize_graphs_to_nquads(&self.named_graphs())
+            .context("Failed to serialize graph to N-Quads")
+    }
+
+    /// Get the RDF/XML serialization of the RDF graph
+    ///
+    /// # Returns
+    /// A `Result` containing the graph as RDF/XML serialization, or an error
+    /// if the graph retrieval fails.
+    pub fn serialize_to_rdfxml(&self) -> Result<String> {
+        serialize_graph_to_rdfxml(&self.graph, &self.iri_base.prefix_map())
+            .context("Failed to serialize graph to RDF/XML")
+    }
+
+    /// Get the TriG serialization of the RDF graph
+    ///
+    /// The triples are placed in a named graph identified by the content URL,
+    /// or in the default graph if no content URL was linked. The provenance graph,
+    /// if any, is named by the content URL with a `#provenance` fragment.
+    ///
+    /// # Returns
+    /// A `Result` containing the graph as TriG serialization, or an error
+    /// if the graph retrieval fails.
+    pub fn serialize_to_trig(&self) -> Result<String> {
+        serialize_graphs_to_trig(&self.named_graphs(), &self.iri_base.prefix_map())
+            .context("Failed to serialize graph to TriG")
+    }
+
+    /// Get the canonical N-Quads serialization of the RDF graph, following RDFC-1.0
+    ///
+    /// Equivalent graphs give identical bytes, whatever their blank node labels.
+    ///
+    /// # Returns
+    /// A `Result` containing the graph as canonical N-Quads, or an error
+    /// if the canonicalization fails.
+    pub fn canonicalize(&self) -> Result<String> {
+        serialize_graph_to_canonical_nquads(&self.graph).context("Failed to canonicalize graph")
+    }
+
+    /// Get the hex-encoded SHA-256 digest of the canonical N-Quads serialization
+    ///
+    /// # Returns
+    /// A `Result` containing the digest of [GraphBuilder::canonicalize], or an error
+    /// if the canonicalization fails.
+    pub fn canonical_digest(&self) -> Result<String> {
+        let canonical = self.canonicalize()?;
+        Ok(sha256_hex(canonical.as_bytes()))
+    }
 }
@@ -0,0 +1,160 @@
+use crate::graph::{
+    namespaces::{cat, cat_resource},
+    prefix_map::{generate_prefix_map, PrefixEntry},
+};
+use anyhow::{Context, Result};
+use sophia::inmem::graph::LightGraph;
+use sophia_api::{prelude::*, term::SimpleTerm};
+
+/// Base IRIs of the minted cat+ resources and of the cat+ ontology.
+///
+/// Graphs are always built with the default namespaces, [cat_resource] and [cat],
+/// and then rebased with [IriBase::rebase_graph] for a deployment.
+/// The hashed part of resource IRIs is kept, so that rebased graphs still merge.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IriBase {
+    /// Namespace of the resources identified by hashed IRIs.
+    pub resource: String,
+    /// Namespace of the cat+ ontology.
+    pub ontology: String,
+}
+
+impl Default for IriBase {
+    fn default() -> Self {
+        Self {
+            resource: cat_resource::ns.as_str().to_string(),
+            ontology: cat::ns.as_str().to_string(),
+        }
+    }
+}
+
+impl IriBase {
+    /// Builds the base IRIs, using the default namespace for any that is not given.
+    ///
+    /// Each base must be an absolute IRI ending with `/` or `#`.
+    pub fn new(resource: Option<&str>, ontology: Option<&str>) -> Result<Self> {
+        let default = Self::default();
+        Ok(Self {
+            resource: match resource {
+                Some(resource) => check_namespace(resource).context("Invalid resource base IRI")?,
+                None => default.resource,
+            },
+            ontology: match ontology {
+                Some(ontology) => check_namespace(ontology).context("Invalid ontology IRI")?,
+                None => default.ontology,
+            },
+        })
+    }
+
+    /// Whether these are the default namespaces.
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Replaces the default namespace of `iri` with the configured one, if any.
+    pub fn rebase_iri(&self, iri: &str) -> String {
+        let default = Self::default();
+        for (from, to) in [(&default.resource, &self.resource), (&default.ontology, &self.ontology)]
+        {
+            if let Some(local_name) = iri.strip_prefix(from.as_str()) {
+                return format!("{}{}", to, local_name);
+            }
+        }
+        iri.to_string()
+    }
+
+    /// Rebases the IRIs of `graph`, built with the default namespaces, with [IriBase::rebase_iri].
+    pub fn rebase_graph(&self, graph: &LightGraph) -> Result<LightGraph> {
+        let mut rebased_graph = LightGraph::new();
+        for triple in graph.triples() {
+            let [subject, predicate, object] = triple?;
+            rebased_graph.insert(
+                self.rebase_term(subject),
+                self.rebase_term(predicate),
+                self.rebase_term(object),
+            )?;
+        }
+        Ok(rebased_graph)
+    }
+
+    fn rebase_term(&self, term: &SimpleTerm) -> SimpleTerm<'static> {
+        match term {
+            SimpleTerm::Iri(iri) => IriRef::new_unchecked(self.rebase_iri(iri.as_str()))
+                .try_into_term()
+                .expect("Failed to convert to SimpleTerm"),
+            _ => term.clone().into_term(),
+        }
+    }
+
+    /// The prefixes of [generate_prefix_map], with `cat:` bound to the configured ontology.
+    pub fn prefix_map(&self) -> Vec<PrefixEntry> {
+        generate_prefix_map()
+            .into_iter()
+            .map(|(prefix, iri)| {
+                let iri = Iri::new_unchecked(self.rebase_iri(iri.as_str()).into_boxed_str());
+                (prefix, iri)
+            })
+            .collect()
+    }
+}
+
+/// Checks that `namespace` is an absolute IRI that local names can be appended to.
+fn check_namespace(namespace: &str) -> Result<String> {
+    Iri::new(namespace).with_context(|| format!("'{}' is not an absolute IRI", namespace))?;
+    if !namespace.ends_with('/') && !namespace.ends_with('#') {
+        anyhow::bail!("'{}' must end with '/' or '#'", namespace);
+    }
+    Ok(namespace.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rdf::rdf_parser::parse_turtle_to_graph;
+    use sophia_isomorphism::isomorphic_graphs;
+
+    #[test]
+    fn test_rebase_graph() -> Result<()> {
+        let graph = parse_turtle_to_graph(
+            r#"
+            PREFIX cat: <http://example.org/catplus/ontology/>
+            PREFIX res: <http://example.org/cat/resource/>
+            res:batch a cat:Batch; cat:hasChemical [ cat:chemicalName "water" ].
+            "#,
+        )?;
+        let iri_base = IriBase::new(
+            Some("https://data.example.com/resource/"),
+            Some("https://w3id.org/catplus/ontology#"),
+        )?;
+
+        let rebased = iri_base.rebase_graph(&graph)?;
+        let expected = parse_turtle_to_graph(
+            r#"
+            PREFIX cat: <https://w3id.org/catplus/ontology#>
+            PREFIX res: <https://data.example.com/resource/>
+            res:batch a cat:Batch; cat:hasChemical [ cat:chemicalName "water" ].
+            "#,
+        )?;
+        assert!(isomorphic_graphs(&rebased, &expected)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_iri_base_defaults() -> Result<()> {
+        let iri_base = IriBase::new(None, None)?;
+        assert!(iri_base.is_default());
+        assert_eq!(iri_base.resource, "http://example.org/cat/resource/");
+        assert_eq!(iri_base.ontology, "http://example.org/catplus/ontology/");
+        assert_eq!(
+            iri_base.rebase_iri("http://example.org/cat/resource/x"),
+            iri_base.resource + "x"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_iri_base() {
+        assert!(IriBase::new(Some("resource/"), None).is_err());
+        assert!(IriBase::new(None, Some("https://w3id.org/catplus")).is_err());
+    }
+}
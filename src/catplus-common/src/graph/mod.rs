@@ -1,5 +1,9 @@
 pub mod graph_builder;
 pub mod insert_into;
+pub mod iri_base;
 pub mod namespaces;
 pub mod prefix_map;
+pub mod provenance;
+pub mod references;
+pub mod skolem;
 pub mod utils;
@@ -10,9 +10,11 @@ pub mod allorole;
 pub mod cat;
 pub mod cat_resource;
 pub mod obo;
+pub mod prov;
 pub mod purl;
 pub mod qb;
 pub mod qudt;
 pub mod qudtext;
 pub mod schema;
+pub mod sh;
 pub mod unit;
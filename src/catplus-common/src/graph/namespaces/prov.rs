@@ -0,0 +1,18 @@
+use lazy_static::lazy_static;
+use sophia::api::ns::Namespace;
+use sophia_api::namespace;
+namespace! {
+    "http://www.w3.org/ns/prov#",
+    Activity,
+    Entity,
+    Person,
+    SoftwareAgent,
+    endedAtTime,
+    generatedAtTime,
+    used,
+    wasAssociatedWith,
+    wasGeneratedBy
+}
+lazy_static! {
+    pub static ref ns: Namespace<&'static str> = Namespace::new(PREFIX.as_str()).unwrap();
+}
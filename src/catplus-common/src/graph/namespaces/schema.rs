@@ -3,10 +3,15 @@ use sophia::api::ns::Namespace;
 use sophia_api::namespace;
 namespace! {
     "https://schema.org/",
+    PropertyValue,
+    additionalProperty,
     contentUrl,
     description,
     keywords,
-    name
+    name,
+    sha256,
+    softwareVersion,
+    value
 }
 lazy_static! {
     pub static ref ns: Namespace<&'static str> = Namespace::new(PREFIX.as_str()).unwrap();
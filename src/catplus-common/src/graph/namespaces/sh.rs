@@ -0,0 +1,93 @@
+use lazy_static::lazy_static;
+use sophia::api::ns::Namespace;
+use sophia_api::namespace;
+namespace! {
+    "http://www.w3.org/ns/shacl#",
+    AndConstraintComponent,
+    BlankNode,
+    BlankNodeOrIRI,
+    BlankNodeOrLiteral,
+    ClassConstraintComponent,
+    DatatypeConstraintComponent,
+    HasValueConstraintComponent,
+    IRI,
+    IRIOrLiteral,
+    InConstraintComponent,
+    Info,
+    Literal,
+    MaxCountConstraintComponent,
+    MaxExclusiveConstraintComponent,
+    MaxInclusiveConstraintComponent,
+    MaxLengthConstraintComponent,
+    MinCountConstraintComponent,
+    MinExclusiveConstraintComponent,
+    MinInclusiveConstraintComponent,
+    MinLengthConstraintComponent,
+    NodeConstraintComponent,
+    NodeKindConstraintComponent,
+    NodeShape,
+    NotConstraintComponent,
+    OrConstraintComponent,
+    PatternConstraintComponent,
+    PropertyShape,
+    ValidationReport,
+    ValidationResult,
+    Violation,
+    Warning,
+    XoneConstraintComponent,
+    alternativePath,
+    and,
+    class,
+    closed,
+    conforms,
+    datatype,
+    deactivated,
+    disjoint,
+    equals,
+    flags,
+    focusNode,
+    hasValue,
+    inversePath,
+    languageIn,
+    lessThan,
+    lessThanOrEquals,
+    maxCount,
+    maxExclusive,
+    maxInclusive,
+    maxLength,
+    message,
+    minCount,
+    minExclusive,
+    minInclusive,
+    minLength,
+    node,
+    nodeKind,
+    not,
+    oneOrMorePath,
+    or,
+    path,
+    pattern,
+    property,
+    qualifiedValueShape,
+    result,
+    resultMessage,
+    resultPath,
+    resultSeverity,
+    severity,
+    sourceConstraintComponent,
+    sourceShape,
+    sparql,
+    targetClass,
+    targetNode,
+    targetObjectsOf,
+    targetSubjectsOf,
+    uniqueLang,
+    value,
+    xone,
+    zeroOrMorePath,
+    zeroOrOnePath;
+    in_, "in"
+}
+lazy_static! {
+    pub static ref ns: Namespace<&'static str> = Namespace::new(PREFIX.as_str()).unwrap();
+}
@@ -1,12 +1,15 @@
 use crate::graph::namespaces::{
     allocom, allodc, allohdf, allohdfcube, alloproc, alloprop, alloqual, allores, allorole, cat,
-    obo, purl, qb, qudt, qudtext, schema, unit,
+    obo, prov, purl, qb, qudt, qudtext, schema, unit,
 };
 use sophia_api::{prefix::Prefix, prelude::Iri};
 
 use lazy_static::lazy_static;
 use sophia::api::ns::Namespace;
 
+/// A prefix with the namespace IRI it abbreviates.
+pub type PrefixEntry = (Prefix<Box<str>>, Iri<Box<str>>);
+
 lazy_static! {
     pub static ref rdf: Namespace<&'static str> =
         Namespace::new("http://www.w3.org/1999/02/22-rdf-syntax-ns#").unwrap();
@@ -34,7 +37,7 @@ macro_rules! ns_entries_module { // For the other modules
     };
 }
 
-pub fn generate_prefix_map() -> Vec<(Prefix<Box<str>>, Iri<Box<str>>)> {
+pub fn generate_prefix_map() -> Vec<PrefixEntry> {
     let msg = "Namespace URI should always be valid";
     ns_entries_direct!(msg, rdf, xsd) // Correct call for rdf and xsd
         .into_iter()
@@ -58,7 +61,8 @@ pub fn generate_prefix_map() -> Vec<(Prefix<Box<str>>, Iri<Box<str>>)> {
                 alloqual,
                 allodc,
                 purl,
-                obo
+                obo,
+                prov
             )
             .into_iter(),
         )
@@ -70,3 +74,14 @@ pub fn generate_prefix_map() -> Vec<(Prefix<Box<str>>, Iri<Box<str>>)> {
         })
         .collect()
 }
+
+/// Generates a JSON-LD `@context` object with the namespaces of `prefix_map`,
+/// such as [generate_prefix_map].
+pub fn generate_jsonld_context(prefix_map: &[PrefixEntry]) -> serde_json::Value {
+    let context: serde_json::Map<String, serde_json::Value> = prefix_map
+        .iter()
+        .map(|(prefix, iri)| (prefix.as_str().to_string(), iri.as_str().into()))
+        .collect();
+
+    serde_json::Value::Object(context)
+}
@@ -0,0 +1,170 @@
+use crate::graph::{
+    insert_into::{InsertIntoGraph, Link},
+    namespaces::{prov, schema},
+    utils::generate_resource_identifier_uri,
+};
+use serde::Deserialize;
+use sophia::{
+    api::ns::{rdf, xsd},
+    inmem::graph::LightGraph,
+};
+use sophia_api::{prelude::*, term::SimpleTerm};
+
+/// Graph holding the provenance triples of a conversion.
+#[derive(Deserialize, Debug, clap::ValueEnum, Clone, Copy, PartialEq, Eq)]
+pub enum ProvenanceGraph {
+    /// The graph of the converted data.
+    Default,
+    /// A separate named graph, `<content URL>#provenance`, serialized with N-Quads and TriG.
+    Named,
+}
+
+/// How a graph was produced: the conversion activity, as a PROV-O `prov:Activity`.
+///
+/// The activity, agents and input file are identified by hashed resource IRIs, so that
+/// merged graphs share them.
+#[derive(Debug, Clone)]
+pub struct Provenance {
+    pub software: Software,
+    /// User running the conversion, if known.
+    pub user: Option<User>,
+    pub input: InputFile,
+    /// End of the conversion, as `xsd:dateTime`.
+    pub ended_at: String,
+    /// Input type and conversion options.
+    pub parameters: Vec<Parameter>,
+}
+
+/// The converter, as a `prov:SoftwareAgent`.
+#[derive(Debug, Clone)]
+pub struct Software {
+    pub name: String,
+    pub version: String,
+}
+
+/// The user running the conversion, as a `prov:Person`.
+#[derive(Debug, Clone)]
+pub struct User {
+    pub name: String,
+}
+
+/// The converted input file, as a `prov:Entity`.
+#[derive(Debug, Clone)]
+pub struct InputFile {
+    pub content_url: Option<String>,
+    /// Hex-encoded SHA-256 digest of the file content.
+    pub sha256: String,
+}
+
+/// A name-value pair, as a `schema:PropertyValue`.
+#[derive(Debug, Clone)]
+pub struct Parameter {
+    pub name: String,
+    pub value: String,
+}
+
+impl Parameter {
+    pub fn new(name: &str, value: impl ToString) -> Self {
+        Self { name: name.to_string(), value: value.to_string() }
+    }
+}
+
+impl InsertIntoGraph for Provenance {
+    fn get_uri(&self) -> SimpleTerm<'static> {
+        // build URI based on the input and the time of the conversion
+        generate_resource_identifier_uri(format!("{}-{}", self.input.sha256, self.ended_at))
+    }
+
+    fn insert_into(&self, graph: &mut LightGraph, iri: SimpleTerm) -> anyhow::Result<()> {
+        for (pred, value) in [
+            (rdf::type_, &prov::Activity.as_simple() as &dyn InsertIntoGraph),
+            (prov::endedAtTime, &(self.ended_at.as_str() * xsd::dateTime).as_simple()),
+            (prov::used, &self.input),
+            (prov::wasAssociatedWith, &self.software),
+            (prov::wasAssociatedWith, &self.user),
+            (schema::additionalProperty, &self.parameters),
+        ] {
+            value.attach_into(
+                graph,
+                Link { source_iri: iri.clone(), pred: pred.as_simple(), target_iri: None },
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl InsertIntoGraph for Software {
+    fn get_uri(&self) -> SimpleTerm<'static> {
+        generate_resource_identifier_uri(format!("{}-{}", self.name, self.version))
+    }
+
+    fn insert_into(&self, graph: &mut LightGraph, iri: SimpleTerm) -> anyhow::Result<()> {
+        for (pred, value) in [
+            (rdf::type_, &prov::SoftwareAgent.as_simple() as &dyn InsertIntoGraph),
+            (schema::name, &self.name.as_simple()),
+            (schema::softwareVersion, &self.version.as_simple()),
+        ] {
+            value.attach_into(
+                graph,
+                Link { source_iri: iri.clone(), pred: pred.as_simple(), target_iri: None },
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl InsertIntoGraph for User {
+    fn get_uri(&self) -> SimpleTerm<'static> {
+        generate_resource_identifier_uri(self.name.clone())
+    }
+
+    fn insert_into(&self, graph: &mut LightGraph, iri: SimpleTerm) -> anyhow::Result<()> {
+        for (pred, value) in [
+            (rdf::type_, &prov::Person.as_simple() as &dyn InsertIntoGraph),
+            (schema::name, &self.name.as_simple()),
+        ] {
+            value.attach_into(
+                graph,
+                Link { source_iri: iri.clone(), pred: pred.as_simple(), target_iri: None },
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl InsertIntoGraph for InputFile {
+    fn get_uri(&self) -> SimpleTerm<'static> {
+        // build URI based on the file content
+        generate_resource_identifier_uri(self.sha256.clone())
+    }
+
+    fn insert_into(&self, graph: &mut LightGraph, iri: SimpleTerm) -> anyhow::Result<()> {
+        for (pred, value) in [
+            (rdf::type_, &prov::Entity.as_simple() as &dyn InsertIntoGraph),
+            (schema::contentUrl, &self.content_url.as_ref().map(|url| url.as_simple())),
+            (schema::sha256, &self.sha256.as_simple()),
+        ] {
+            value.attach_into(
+                graph,
+                Link { source_iri: iri.clone(), pred: pred.as_simple(), target_iri: None },
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl InsertIntoGraph for Parameter {
+    fn insert_into(&self, graph: &mut LightGraph, iri: SimpleTerm) -> anyhow::Result<()> {
+        for (pred, value) in [
+            (rdf::type_, &schema::PropertyValue.as_simple() as &dyn InsertIntoGraph),
+            (schema::name, &self.name.as_simple()),
+            (schema::value, &self.value.as_simple()),
+        ] {
+            value.attach_into(
+                graph,
+                Link { source_iri: iri.clone(), pred: pred.as_simple(), target_iri: None },
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,119 @@
+use crate::graph::{iri_base::IriBase, namespaces::purl};
+use anyhow::Result;
+use sophia::inmem::graph::LightGraph;
+use sophia_api::{ns::rdf, prelude::*, term::SimpleTerm};
+use std::{collections::BTreeSet, fmt};
+
+/// A reference between resources that is not resolved within a graph.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DanglingReference {
+    /// A cat+ resource used as object, but never described as subject.
+    Undescribed { iri: String, predicate: String },
+    /// A `cat:Product` that no action produces with `cat:producesProduct`.
+    UnproducedProduct { iri: String, identifier: Option<String> },
+}
+
+impl fmt::Display for DanglingReference {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DanglingReference::Undescribed { iri, predicate } => {
+                write!(f, "<{}> is referenced with <{}> but never described", iri, predicate)
+            }
+            DanglingReference::UnproducedProduct { iri, identifier: Some(identifier) } => {
+                write!(f, "product '{}' <{}> is not produced by any action", identifier, iri)
+            }
+            DanglingReference::UnproducedProduct { iri, identifier: None } => {
+                write!(f, "product <{}> is not produced by any action", iri)
+            }
+        }
+    }
+}
+
+/// Finds the references of `graph` that do not resolve to a resource of the graph.
+///
+/// Resources of different files only connect through their hashed IRIs, so that
+/// these references usually point to a missing or mismatching input file.
+/// Resources and ontology terms are looked up in the namespaces of `iri_base`.
+pub fn find_dangling_references(
+    graph: &LightGraph,
+    iri_base: &IriBase,
+) -> Result<Vec<DanglingReference>> {
+    let resource_ns = iri_base.resource.as_str();
+    let product_class = format!("{}Product", iri_base.ontology);
+    let product_class = IriRef::new_unchecked(product_class.as_str());
+    let produces_product = format!("{}producesProduct", iri_base.ontology);
+    let produces_product = IriRef::new_unchecked(produces_product.as_str());
+    let mut subjects = BTreeSet::new();
+    for triple in graph.triples() {
+        let [subject, _, _] = triple?;
+        subjects.insert(subject.clone());
+    }
+
+    let mut dangling = BTreeSet::new();
+    for triple in graph.triples() {
+        let [_, predicate, object] = triple?;
+        if let SimpleTerm::Iri(iri) = object {
+            if iri.as_str().starts_with(resource_ns) && !subjects.contains(object) {
+                dangling.insert(DanglingReference::Undescribed {
+                    iri: iri.as_str().to_string(),
+                    predicate: predicate.iri().map(|p| p.as_str().to_string()).unwrap_or_default(),
+                });
+            }
+        }
+    }
+
+    for triple in graph.triples_matching(Any, [rdf::type_], [product_class]) {
+        let [product, _, _] = triple?;
+        if graph.triples_matching(Any, [produces_product], [product]).next().is_some() {
+            continue;
+        }
+        let identifier = graph
+            .triples_matching([product], [purl::identifier], Any)
+            .filter_map(Result::ok)
+            .find_map(|[_, _, identifier]| identifier.lexical_form().map(|l| l.to_string()));
+        let iri = match product {
+            SimpleTerm::Iri(iri) => iri.as_str().to_string(),
+            SimpleTerm::BlankNode(bnode) => format!("_:{}", bnode.as_str()),
+            _ => format!("{:?}", product),
+        };
+        dangling.insert(DanglingReference::UnproducedProduct { iri, identifier });
+    }
+
+    Ok(dangling.into_iter().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rdf::rdf_parser::parse_turtle_to_graph;
+
+    #[test]
+    fn test_find_dangling_references() -> Result<()> {
+        let graph = parse_turtle_to_graph(
+            r#"
+            PREFIX cat: <http://example.org/catplus/ontology/>
+            PREFIX purl: <http://purl.allotrope.org/ontologies/>
+            PREFIX res: <http://example.org/cat/resource/>
+            [] a cat:SynthAddAction; cat:producesProduct res:produced; cat:hasBatch res:batch.
+            res:produced a cat:Product; purl:identifier "produced".
+            res:measured a cat:Product; purl:identifier "measured".
+            "#,
+        )?;
+
+        let dangling = find_dangling_references(&graph, &IriBase::default())?;
+        assert_eq!(
+            dangling,
+            vec![
+                DanglingReference::Undescribed {
+                    iri: "http://example.org/cat/resource/batch".to_string(),
+                    predicate: "http://example.org/catplus/ontology/hasBatch".to_string(),
+                },
+                DanglingReference::UnproducedProduct {
+                    iri: "http://example.org/cat/resource/measured".to_string(),
+                    identifier: Some("measured".to_string()),
+                },
+            ]
+        );
+        Ok(())
+    }
+}
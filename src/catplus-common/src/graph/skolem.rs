@@ -0,0 +1,134 @@
+use crate::graph::namespaces::cat_resource;
+use anyhow::{Context, Result};
+use sophia::inmem::graph::LightGraph;
+use sophia_api::{
+    prelude::*,
+    term::{bnode_id::BnodeId, SimpleTerm},
+};
+
+/// Path of skolem IRIs, as recommended by RDF 1.1.
+pub const GENID_PATH: &str = "/.well-known/genid/";
+
+/// Returns the skolem IRI `<base>/.well-known/genid/<bnode_id>`.
+pub fn skolem_iri(base: &str, bnode_id: &str) -> String {
+    format!("{}{}{}", base.trim_end_matches('/'), GENID_PATH, bnode_id)
+}
+
+/// Returns the scheme and authority of the cat+ resource namespace, used as skolem base.
+pub fn default_skolem_base() -> String {
+    skolem_base(cat_resource::ns.as_str())
+}
+
+/// Returns the scheme and authority of `namespace`, used as skolem base.
+pub fn skolem_base(namespace: &str) -> String {
+    let authority_start = namespace.find("://").map_or(0, |index| index + 3);
+    match namespace[authority_start..].find('/') {
+        Some(index) => namespace[..authority_start + index].to_string(),
+        None => namespace.to_string(),
+    }
+}
+
+/// Replaces the blank nodes of `graph` with skolem IRIs under `base`.
+pub fn skolemize_graph(graph: &LightGraph, base: &str) -> Result<LightGraph> {
+    let mut skolemized_graph = LightGraph::new();
+
+    for triple in graph.triples() {
+        let [subject, predicate, object] = triple?;
+        let subject = match subject {
+            SimpleTerm::BlankNode(_) | SimpleTerm::Iri(_) => skolemize_term(subject, base)?,
+            _ => anyhow::bail!("Unexpected subject {:?}, expected an IRI or a blank node", subject),
+        };
+        let object = skolemize_term(object, base)?;
+        skolemized_graph.insert(subject, predicate, object)?;
+    }
+
+    Ok(skolemized_graph)
+}
+
+/// Replaces the skolem IRIs of `graph` with blank nodes, reversing [skolemize_graph].
+///
+/// Any IRI with a `/.well-known/genid/` path is replaced, whatever its base.
+pub fn deskolemize_graph(graph: &LightGraph) -> Result<LightGraph> {
+    let mut deskolemized_graph = LightGraph::new();
+
+    for triple in graph.triples() {
+        let [subject, predicate, object] = triple?;
+        let subject = deskolemize_term(subject)?;
+        let object = deskolemize_term(object)?;
+        deskolemized_graph.insert(subject, predicate, object)?;
+    }
+
+    Ok(deskolemized_graph)
+}
+
+fn skolemize_term(term: &SimpleTerm, base: &str) -> Result<SimpleTerm<'static>> {
+    match term {
+        SimpleTerm::BlankNode(bnode) => {
+            let iri = skolem_iri(base, bnode.as_str());
+            let iri = IriRef::new(iri.clone())
+                .with_context(|| format!("Invalid skolem IRI '{}'", iri))?;
+            Ok(iri.into_term())
+        }
+        _ => Ok(term.clone().into_term()),
+    }
+}
+
+fn deskolemize_term(term: &SimpleTerm) -> Result<SimpleTerm<'static>> {
+    match term {
+        SimpleTerm::Iri(iri) => match iri.as_str().split_once(GENID_PATH) {
+            Some((_, bnode_id)) => {
+                let bnode = BnodeId::new(bnode_id.to_string()).map_err(|_| {
+                    anyhow::anyhow!(
+                        "Invalid blank node identifier '{}' in skolem IRI '{}'",
+                        bnode_id,
+                        iri.as_str()
+                    )
+                })?;
+                Ok(bnode.into_term())
+            }
+            None => Ok(term.clone().into_term()),
+        },
+        _ => Ok(term.clone().into_term()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rdf::rdf_parser::parse_turtle_to_graph;
+    use sophia_isomorphism::isomorphic_graphs;
+
+    #[test]
+    fn test_skolemize_roundtrip() -> Result<()> {
+        let graph = parse_turtle_to_graph(
+            r#"
+            PREFIX ex: <http://example.org/>
+            ex:a ex:p _:b1 .
+            _:b1 ex:q "value"; ex:r _:b2 .
+            "#,
+        )?;
+
+        let skolemized = skolemize_graph(&graph, "https://example.org/")?;
+        for triple in skolemized.triples() {
+            let [subject, _, object] = triple?;
+            assert!(!subject.is_blank_node() && !object.is_blank_node());
+        }
+        let genid = skolem_iri("https://example.org/", "b1");
+        assert_eq!(genid, "https://example.org/.well-known/genid/b1");
+        let genid = IriRef::new_unchecked(genid.as_str());
+        assert_eq!(skolemized.triples_matching([genid], Any, Any).count(), 2);
+
+        let deskolemized = deskolemize_graph(&skolemized)?;
+        assert!(isomorphic_graphs(&graph, &deskolemized)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_default_skolem_base() {
+        assert_eq!(default_skolem_base(), "http://example.org");
+        assert_eq!(
+            skolem_base("https://data.example.com/cat/resource/"),
+            "https://data.example.com"
+        );
+    }
+}
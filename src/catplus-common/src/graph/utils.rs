@@ -1,10 +1,13 @@
 use crate::graph::namespaces::cat_resource;
+use anyhow::Result;
 use base64::{engine::general_purpose, Engine as _};
 use sha2::{Digest, Sha256};
+use sophia::inmem::graph::LightGraph;
 use sophia_api::{
     prelude::*,
     term::{bnode_id::BnodeId, SimpleTerm},
 };
+use std::collections::{HashMap, HashSet};
 use uuid::Uuid;
 
 pub fn generate_bnode_term() -> SimpleTerm<'static> {
@@ -22,8 +25,207 @@ pub(crate) fn hash_identifier(identifier: &str) -> String {
     general_purpose::URL_SAFE_NO_PAD.encode(result)
 }
 
+/// Hashes content into a hex-encoded SHA-256 digest.
+pub fn sha256_hex(content: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(content))
+}
+
 pub fn generate_resource_identifier_uri(resource_id: String) -> SimpleTerm<'static> {
     let mut uri = cat_resource::ns.clone().as_str().to_owned();
     uri.push_str(&hash_identifier(&resource_id));
     IriRef::new_unchecked(uri).try_into_term().expect("Failed to convert to SimpleTerm")
 }
+
+/// Relabels the blank nodes of `graph` with identifiers derived from the graph itself.
+///
+/// The identifier of a blank node is the hash of the subjects and predicates linking to it
+/// and of its own content, where nested blank nodes are described by their content.
+/// The same input thus always gives the same identifiers, whatever labels it had before.
+/// Blank nodes with the same parents and content are told apart by their order in the graph.
+pub fn relabel_blank_nodes(graph: &LightGraph) -> Result<LightGraph> {
+    let triples = graph
+        .triples()
+        .map(|triple| triple.map(|[s, p, o]| [s.clone(), p.clone(), o.clone()]))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut labeler = BnodeLabeler::new(&triples);
+
+    let mut relabeled_graph = LightGraph::new();
+    for [subject, predicate, object] in &triples {
+        let subject = labeler.relabel(subject);
+        let object = labeler.relabel(object);
+        relabeled_graph.insert(subject, predicate, object)?;
+    }
+    Ok(relabeled_graph)
+}
+
+/// Computes the deterministic labels of [relabel_blank_nodes], and the content hashes
+/// that the graph diff uses to align blank nodes.
+pub(crate) struct BnodeLabeler<'a> {
+    triples: &'a [[SimpleTerm<'a>; 3]],
+    /// Indices of the triples with a blank node as subject.
+    outgoing: HashMap<&'a str, Vec<usize>>,
+    /// Indices of the triples with a blank node as object.
+    incoming: HashMap<&'a str, Vec<usize>>,
+    contents: HashMap<&'a str, String>,
+    labels: HashMap<&'a str, String>,
+    /// Number of blank nodes labeled for each hashed description.
+    occurrences: HashMap<String, usize>,
+    /// Blank nodes whose content is being hashed.
+    visiting: HashSet<&'a str>,
+    /// Blank nodes whose label is being computed, to detect cycles through their parents.
+    labeling: HashSet<&'a str>,
+}
+
+impl<'a> BnodeLabeler<'a> {
+    pub(crate) fn new(triples: &'a [[SimpleTerm<'a>; 3]]) -> Self {
+        let mut outgoing: HashMap<&str, Vec<usize>> = HashMap::new();
+        let mut incoming: HashMap<&str, Vec<usize>> = HashMap::new();
+        for (index, [subject, _, object]) in triples.iter().enumerate() {
+            if let SimpleTerm::BlankNode(bnode) = subject {
+                outgoing.entry(bnode.as_str()).or_default().push(index);
+            }
+            if let SimpleTerm::BlankNode(bnode) = object {
+                incoming.entry(bnode.as_str()).or_default().push(index);
+            }
+        }
+        Self {
+            triples,
+            outgoing,
+            incoming,
+            contents: HashMap::new(),
+            labels: HashMap::new(),
+            occurrences: HashMap::new(),
+            visiting: HashSet::new(),
+            labeling: HashSet::new(),
+        }
+    }
+
+    /// Returns `term` with its deterministic label if it is a blank node.
+    fn relabel(&mut self, term: &'a SimpleTerm<'a>) -> SimpleTerm<'static> {
+        match term {
+            SimpleTerm::BlankNode(bnode) => {
+                let label = self.label(bnode.as_str());
+                BnodeId::new_unchecked(label).into_term()
+            }
+            _ => term.clone().into_term(),
+        }
+    }
+
+    /// Hash of the outgoing triples of a blank node, with nested blank nodes
+    /// replaced by their own content hash.
+    pub(crate) fn content(&mut self, bnode: &'a str) -> String {
+        if let Some(content) = self.contents.get(bnode) {
+            return content.clone();
+        }
+        // Blank nodes built from JSON form a tree, but guard against cycles.
+        if !self.visiting.insert(bnode) {
+            return String::new();
+        }
+        let mut lines = Vec::new();
+        for index in self.outgoing.get(bnode).cloned().unwrap_or_default() {
+            let [_, predicate, object] = &self.triples[index];
+            let object_key = match object {
+                SimpleTerm::BlankNode(child) => format!("_:{}", self.content(child.as_str())),
+                _ => term_key(object),
+            };
+            lines.push(format!("{} {}", term_key(predicate), object_key));
+        }
+        lines.sort();
+        self.visiting.remove(bnode);
+
+        let content = hash_identifier(&lines.join("\n"));
+        self.contents.insert(bnode, content.clone());
+        content
+    }
+
+    /// Label of a blank node, from the subjects and predicates linking to it and its content.
+    fn label(&mut self, bnode: &'a str) -> String {
+        if let Some(label) = self.labels.get(bnode) {
+            return label.clone();
+        }
+        self.labeling.insert(bnode);
+        let mut lines = Vec::new();
+        for index in self.incoming.get(bnode).cloned().unwrap_or_default() {
+            let [subject, predicate, _] = &self.triples[index];
+            let subject_key = match subject {
+                SimpleTerm::BlankNode(parent) => format!("_:{}", self.parent_key(parent.as_str())),
+                _ => term_key(subject),
+            };
+            lines.push(format!("{} {}", subject_key, term_key(predicate)));
+        }
+        lines.sort();
+        lines.push(self.content(bnode));
+        self.labeling.remove(bnode);
+        let mut description = lines.join("\n");
+        let occurrence = self.occurrences.entry(description.clone()).or_default();
+        if *occurrence > 0 {
+            description.push_str(&format!("\n{}", occurrence));
+        }
+        *occurrence += 1;
+
+        // Labels must not start with '-', which base64 may produce.
+        let label = format!("b{}", hash_identifier(&description));
+        self.labels.insert(bnode, label.clone());
+        label
+    }
+
+    /// Key of a parent blank node: its label, or its content within a cycle.
+    fn parent_key(&mut self, parent: &'a str) -> String {
+        if self.labeling.contains(parent) {
+            return self.content(parent);
+        }
+        self.label(parent)
+    }
+}
+
+/// N-Triples like key of a term, without blank node labels.
+fn term_key(term: &SimpleTerm) -> String {
+    match term {
+        SimpleTerm::Iri(iri) => format!("<{}>", iri.as_str()),
+        SimpleTerm::LiteralDatatype(lexical, datatype) => {
+            format!("{:?}^^<{}>", lexical.as_ref(), datatype.as_str())
+        }
+        SimpleTerm::LiteralLanguage(lexical, tag) => {
+            format!("{:?}@{}", lexical.as_ref(), tag.as_str())
+        }
+        _ => format!("{:?}", term),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rdf::{
+        rdf_parser::parse_turtle_to_graph, rdf_serializers::serialize_graph_to_ntriples,
+    };
+    use sophia_isomorphism::isomorphic_graphs;
+
+    fn sorted_ntriples(graph: &LightGraph) -> Result<Vec<String>> {
+        let mut lines: Vec<String> =
+            serialize_graph_to_ntriples(graph)?.lines().map(str::to_owned).collect();
+        lines.sort();
+        Ok(lines)
+    }
+
+    #[test]
+    fn test_relabel_blank_nodes_triple_order() -> Result<()> {
+        // Parents linked by the same subject and predicate differ only by their content.
+        let graph = parse_turtle_to_graph(
+            r#"
+            @prefix ex: <http://example.org/> .
+            ex:s ex:p [ ex:v 1 ; ex:c [ ex:w 1 ] ] , [ ex:v 2 ; ex:c [ ex:w 1 ] ] .
+            "#,
+        )?;
+        let triples = graph.triples().collect::<Result<Vec<_>, _>>()?;
+        let mut reversed = LightGraph::new();
+        for [s, p, o] in triples.into_iter().rev() {
+            reversed.insert(s, p, o)?;
+        }
+
+        let relabeled = relabel_blank_nodes(&graph)?;
+        let relabeled_reversed = relabel_blank_nodes(&reversed)?;
+        assert!(isomorphic_graphs(&relabeled, &relabeled_reversed)?);
+        assert_eq!(sorted_ntriples(&relabeled)?, sorted_ntriples(&relabeled_reversed)?);
+        Ok(())
+    }
+}
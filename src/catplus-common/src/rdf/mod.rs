@@ -1,2 +1,3 @@
+pub mod rdf_diff;
 pub mod rdf_parser;
 pub mod rdf_serializers;
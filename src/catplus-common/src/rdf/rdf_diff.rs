@@ -0,0 +1,464 @@
+use crate::{
+    graph::{prefix_map::generate_prefix_map, utils::BnodeLabeler},
+    rdf::rdf_serializers::compact_iri,
+};
+use anyhow::Result;
+use sophia::inmem::graph::LightGraph;
+use sophia_api::{prelude::*, term::SimpleTerm};
+use sophia_isomorphism::isomorphic_graphs;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fmt,
+};
+
+/// Whether a triple was added to or removed from a graph.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Change {
+    Removed,
+    Added,
+}
+
+/// A triple found in only one of the compared graphs, with compacted terms.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TripleChange {
+    pub subject: String,
+    pub predicate: String,
+    pub object: String,
+    pub change: Change,
+}
+
+/// The differences between two graphs, see [diff_graphs].
+#[derive(Debug, Default)]
+pub struct GraphDiff {
+    /// Changed triples grouped by the type of their subject, sorted by subject.
+    pub groups: BTreeMap<String, Vec<TripleChange>>,
+}
+
+impl GraphDiff {
+    /// Whether the graphs are isomorphic.
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+
+    pub fn added_count(&self) -> usize {
+        self.count(Change::Added)
+    }
+
+    pub fn removed_count(&self) -> usize {
+        self.count(Change::Removed)
+    }
+
+    fn count(&self, change: Change) -> usize {
+        self.groups.values().flatten().filter(|triple| triple.change == change).count()
+    }
+}
+
+impl fmt::Display for GraphDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (subject_type, triples) in &self.groups {
+            writeln!(f, "{}", subject_type)?;
+            for triple in triples {
+                let sign = match triple.change {
+                    Change::Removed => '-',
+                    Change::Added => '+',
+                };
+                writeln!(
+                    f,
+                    "  {} {} {} {} .",
+                    sign, triple.subject, triple.predicate, triple.object
+                )?;
+            }
+        }
+        write!(f, "{} added, {} removed.", self.added_count(), self.removed_count())
+    }
+}
+
+/// Compares two graphs and returns the triples only found in one of them.
+///
+/// Blank nodes are aligned by structure: a blank node of `new` takes the label of the
+/// blank node of `old` with the same content, nested blank nodes included, or else with
+/// the same types and the most triples in common, so that a changed blank node shows as
+/// changed triples instead of a whole new subtree.
+/// The blank nodes are labeled `_:b0`, `_:b1`... in order of appearance in `old`.
+pub fn diff_graphs(old: &LightGraph, new: &LightGraph) -> Result<GraphDiff> {
+    if isomorphic_graphs(old, new)? {
+        return Ok(GraphDiff::default());
+    }
+    let namespaces: Vec<(String, String)> = generate_prefix_map()
+        .into_iter()
+        .map(|(prefix, iri)| (prefix.as_str().to_string(), iri.as_str().to_string()))
+        .collect();
+    let old = IndexedGraph::new(old, &namespaces)?;
+    let new = IndexedGraph::new(new, &namespaces)?;
+
+    let new_to_old = align_blank_nodes(&old, &new);
+    let mut next_label = old.bnode_count;
+    let new_labels: Vec<usize> = new_to_old
+        .iter()
+        .map(|matched| {
+            matched.unwrap_or_else(|| {
+                next_label += 1;
+                next_label - 1
+            })
+        })
+        .collect();
+
+    let old_triples = old.render(|bnode| bnode);
+    let new_triples = new.render(|bnode| new_labels[bnode]);
+
+    let mut diff = GraphDiff::default();
+    for (triples, others, types, change) in [
+        (&old_triples, &new_triples, old.types_by_subject(&old_triples), Change::Removed),
+        (&new_triples, &old_triples, new.types_by_subject(&new_triples), Change::Added),
+    ] {
+        for [subject, predicate, object] in triples.difference(others) {
+            let subject_type = types.get(subject).cloned().unwrap_or("(untyped)".to_string());
+            diff.groups.entry(subject_type).or_default().push(TripleChange {
+                subject: subject.clone(),
+                predicate: predicate.clone(),
+                object: object.clone(),
+                change,
+            });
+        }
+    }
+    for triples in diff.groups.values_mut() {
+        triples.sort();
+    }
+
+    Ok(diff)
+}
+
+/// A node of an [IndexedGraph]: a compacted term, or the index of a blank node.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Node {
+    Term(String),
+    Bnode(usize),
+}
+
+/// A graph with compacted terms and indexed blank nodes.
+struct IndexedGraph {
+    triples: Vec<(Node, String, Node)>,
+    bnode_count: usize,
+    /// Hash of the content of each blank node, with nested blank nodes described by their content.
+    contents: Vec<String>,
+    /// Indices of the triples with each blank node as subject.
+    outgoing: Vec<Vec<usize>>,
+    /// Indices of the triples with each blank node as object.
+    incoming: Vec<Vec<usize>>,
+}
+
+impl IndexedGraph {
+    fn new(graph: &LightGraph, namespaces: &[(String, String)]) -> Result<Self> {
+        let mut bnodes: HashMap<String, usize> = HashMap::new();
+        let mut node = |term: &SimpleTerm| match term {
+            SimpleTerm::BlankNode(bnode) => {
+                let index = bnodes.len();
+                Node::Bnode(*bnodes.entry(bnode.as_str().to_string()).or_insert(index))
+            }
+            _ => Node::Term(term_string(term, namespaces)),
+        };
+
+        let mut triples = Vec::new();
+        let mut terms = Vec::new();
+        for triple in graph.triples() {
+            let [subject, predicate, object] = triple?;
+            triples.push((node(subject), term_string(predicate, namespaces), node(object)));
+            terms.push([subject.clone(), predicate.clone(), object.clone()]);
+        }
+
+        let bnode_count = bnodes.len();
+        let mut labeler = BnodeLabeler::new(&terms);
+        let mut contents = vec![String::new(); bnode_count];
+        for (bnode, &index) in &bnodes {
+            contents[index] = labeler.content(bnode);
+        }
+        let mut outgoing = vec![Vec::new(); bnode_count];
+        let mut incoming = vec![Vec::new(); bnode_count];
+        for (index, (subject, _, object)) in triples.iter().enumerate() {
+            if let Node::Bnode(bnode) = subject {
+                outgoing[*bnode].push(index);
+            }
+            if let Node::Bnode(bnode) = object {
+                incoming[*bnode].push(index);
+            }
+        }
+
+        Ok(Self { triples, bnode_count, contents, outgoing, incoming })
+    }
+
+    /// Sorted types of a blank node.
+    fn bnode_types(&self, bnode: usize) -> Vec<&str> {
+        let mut types: Vec<&str> = self.outgoing[bnode]
+            .iter()
+            .filter_map(|&index| match &self.triples[index] {
+                (_, predicate, Node::Term(object)) if predicate == "rdf:type" => {
+                    Some(object.as_str())
+                }
+                _ => None,
+            })
+            .collect();
+        types.sort();
+        types
+    }
+
+    /// Sorted structural features of a blank node: its outgoing and incoming triples,
+    /// where blank nodes are replaced by their aligned label, if any.
+    fn features(&self, bnode: usize, label: impl Fn(usize) -> Option<usize>) -> Vec<String> {
+        let key = |node: &Node| match node {
+            Node::Term(term) => term.clone(),
+            Node::Bnode(other) => match label(*other) {
+                Some(label) => format!("_:b{}", label),
+                None => "_:?".to_string(),
+            },
+        };
+        let mut features: Vec<String> = self.outgoing[bnode]
+            .iter()
+            .map(|&index| {
+                let (_, predicate, object) = &self.triples[index];
+                format!("> {} {}", predicate, key(object))
+            })
+            .chain(self.incoming[bnode].iter().map(|&index| {
+                let (subject, predicate, _) = &self.triples[index];
+                format!("< {} {}", key(subject), predicate)
+            }))
+            .collect();
+        features.sort();
+        features
+    }
+
+    /// Renders the triples, with blank nodes labeled by `label`.
+    fn render(&self, label: impl Fn(usize) -> usize) -> BTreeSet<[String; 3]> {
+        let render = |node: &Node| match node {
+            Node::Term(term) => term.clone(),
+            Node::Bnode(bnode) => format!("_:b{}", label(*bnode)),
+        };
+        self.triples
+            .iter()
+            .map(|(subject, predicate, object)| {
+                [render(subject), predicate.clone(), render(object)]
+            })
+            .collect()
+    }
+
+    /// Types of the rendered subjects, joined into a single string.
+    fn types_by_subject(&self, triples: &BTreeSet<[String; 3]>) -> HashMap<String, String> {
+        let mut types: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        for [subject, predicate, object] in triples {
+            if predicate == "rdf:type" {
+                types.entry(subject).or_default().push(object);
+            }
+        }
+        types.into_iter().map(|(subject, types)| (subject.to_string(), types.join(", "))).collect()
+    }
+}
+
+/// Aligns the blank nodes of `new` with those of `old`.
+///
+/// Blank nodes whose content is found once in each graph are matched first. The other
+/// pairs of blank nodes with the same types are matched when they are each other's best
+/// candidate, by number of common features. Matching is repeated until no new pair is
+/// found, as matched blank nodes make the features of their neighbours comparable.
+/// Tied candidates are only matched when no other pair can be, so that they are told
+/// apart by their aligned neighbours first.
+fn align_blank_nodes(old: &IndexedGraph, new: &IndexedGraph) -> Vec<Option<usize>> {
+    let mut old_to_new: Vec<Option<usize>> = vec![None; old.bnode_count];
+    let mut new_to_old: Vec<Option<usize>> = vec![None; new.bnode_count];
+
+    let mut contents: HashMap<&str, (Vec<usize>, Vec<usize>)> = HashMap::new();
+    for (bnode, content) in old.contents.iter().enumerate() {
+        contents.entry(content).or_default().0.push(bnode);
+    }
+    for (bnode, content) in new.contents.iter().enumerate() {
+        contents.entry(content).or_default().1.push(bnode);
+    }
+    for (old_bnodes, new_bnodes) in contents.values() {
+        if let ([old_bnode], [new_bnode]) = (old_bnodes.as_slice(), new_bnodes.as_slice()) {
+            old_to_new[*old_bnode] = Some(*new_bnode);
+            new_to_old[*new_bnode] = Some(*old_bnode);
+        }
+    }
+
+    // Candidates must have the same types.
+    let mut buckets: BTreeMap<Vec<&str>, (Vec<usize>, Vec<usize>)> = BTreeMap::new();
+    for bnode in 0..old.bnode_count {
+        buckets.entry(old.bnode_types(bnode)).or_default().0.push(bnode);
+    }
+    for bnode in 0..new.bnode_count {
+        buckets.entry(new.bnode_types(bnode)).or_default().1.push(bnode);
+    }
+
+    let mut allow_ties = false;
+    loop {
+        let mut matches = Vec::new();
+        for (old_bnodes, new_bnodes) in buckets.values() {
+            let old_bnodes: Vec<usize> =
+                old_bnodes.iter().copied().filter(|&bnode| old_to_new[bnode].is_none()).collect();
+            let new_bnodes: Vec<usize> =
+                new_bnodes.iter().copied().filter(|&bnode| new_to_old[bnode].is_none()).collect();
+            if old_bnodes.is_empty() || new_bnodes.is_empty() {
+                continue;
+            }
+
+            let old_features: Vec<Vec<String>> = old_bnodes
+                .iter()
+                .map(|&bnode| old.features(bnode, |other| old_to_new[other].map(|_| other)))
+                .collect();
+            let new_features: Vec<Vec<String>> = new_bnodes
+                .iter()
+                .map(|&bnode| new.features(bnode, |other| new_to_old[other]))
+                .collect();
+
+            let scores: Vec<Vec<usize>> = old_features
+                .iter()
+                .map(|old| new_features.iter().map(|new| common_count(old, new)).collect())
+                .collect();
+            let best_new: Vec<Option<usize>> =
+                scores.iter().map(|row| best_index(row, allow_ties)).collect();
+            for (j, &new_bnode) in new_bnodes.iter().enumerate() {
+                let column: Vec<usize> = scores.iter().map(|row| row[j]).collect();
+                if let Some(i) = best_index(&column, allow_ties) {
+                    if best_new[i] == Some(j) {
+                        matches.push((old_bnodes[i], new_bnode));
+                    }
+                }
+            }
+        }
+
+        if matches.is_empty() {
+            if allow_ties {
+                break;
+            }
+            allow_ties = true;
+            continue;
+        }
+        for (old_bnode, new_bnode) in matches {
+            old_to_new[old_bnode] = Some(new_bnode);
+            new_to_old[new_bnode] = Some(old_bnode);
+        }
+        allow_ties = false;
+    }
+
+    new_to_old
+}
+
+/// Index of the highest non-zero score. On ties, the first one if `allow_ties` is set.
+fn best_index(scores: &[usize], allow_ties: bool) -> Option<usize> {
+    let best = scores.iter().copied().max().filter(|&score| score > 0)?;
+    let mut indices = (0..scores.len()).filter(|&index| scores[index] == best);
+    let index = indices.next()?;
+    match indices.next() {
+        Some(_) if !allow_ties => None,
+        _ => Some(index),
+    }
+}
+
+/// Number of common elements of two sorted lists.
+fn common_count(left: &[String], right: &[String]) -> usize {
+    let (mut i, mut j, mut count) = (0, 0, 0);
+    while i < left.len() && j < right.len() {
+        match left[i].cmp(&right[j]) {
+            std::cmp::Ordering::Less => i += 1,
+            std::cmp::Ordering::Greater => j += 1,
+            std::cmp::Ordering::Equal => {
+                count += 1;
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    count
+}
+
+/// Turtle-like string of a term, with IRIs compacted using the prefix map.
+fn term_string(term: &SimpleTerm, namespaces: &[(String, String)]) -> String {
+    let iri = |iri: &str| {
+        let compacted = compact_iri(iri, namespaces);
+        if compacted == iri {
+            format!("<{}>", iri)
+        } else {
+            compacted
+        }
+    };
+    match term {
+        SimpleTerm::Iri(value) => iri(value.as_str()),
+        SimpleTerm::BlankNode(bnode) => format!("_:{}", bnode.as_str()),
+        SimpleTerm::LiteralDatatype(lexical, datatype)
+            if datatype.as_str() == "http://www.w3.org/2001/XMLSchema#string" =>
+        {
+            format!("{:?}", lexical.as_ref())
+        }
+        SimpleTerm::LiteralDatatype(lexical, datatype) => {
+            format!("{:?}^^{}", lexical.as_ref(), iri(datatype.as_str()))
+        }
+        SimpleTerm::LiteralLanguage(lexical, tag) => {
+            format!("{:?}@{}", lexical.as_ref(), tag.as_str())
+        }
+        _ => format!("{:?}", term),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rdf::rdf_parser::parse_turtle_to_graph;
+
+    const OLD: &str = r#"
+        PREFIX cat: <http://example.org/catplus/ontology/>
+        PREFIX qudt: <http://qudt.org/schema/qudt/>
+        <http://example.org/batch> a cat:Batch;
+            cat:hasWell [ a cat:Well; qudt:value 1 ], [ a cat:Well; qudt:value 2 ].
+    "#;
+
+    #[test]
+    fn test_diff_isomorphic_graphs() -> Result<()> {
+        let old = parse_turtle_to_graph(OLD)?;
+        let new = parse_turtle_to_graph(&OLD.replace("value 1 ], [", "value 1 ] , ["))?;
+        let diff = diff_graphs(&old, &new)?;
+        assert!(diff.is_empty(), "{}", diff);
+        Ok(())
+    }
+
+    #[test]
+    fn test_diff_swapped_nested_blank_nodes() -> Result<()> {
+        let wells = |first: u32, second: u32| {
+            format!(
+                r#"
+                PREFIX cat: <http://example.org/catplus/ontology/>
+                PREFIX qudt: <http://qudt.org/schema/qudt/>
+                <http://example.org/batch> cat:hasWell
+                    [ a cat:Well; cat:hasMeasurement [ qudt:value {} ] ],
+                    [ a cat:Well; cat:hasMeasurement [ qudt:value {} ] ].
+                "#,
+                first, second
+            )
+        };
+        let old = parse_turtle_to_graph(&wells(1, 2))?;
+        let new = parse_turtle_to_graph(&wells(2, 1))?;
+        let diff = diff_graphs(&old, &new)?;
+        assert!(diff.is_empty(), "{}", diff);
+
+        // Without the early return, the aligned blank nodes give no differences either.
+        let new_changed = parse_turtle_to_graph(&wells(2, 1).replace(
+            "<http://example.org/batch> cat:hasWell",
+            "<http://example.org/batch> cat:name \"batch\"; cat:hasWell",
+        ))?;
+        let diff = diff_graphs(&old, &new_changed)?;
+        assert_eq!((diff.added_count(), diff.removed_count()), (1, 0), "{}", diff);
+        Ok(())
+    }
+
+    #[test]
+    fn test_diff_changed_blank_node() -> Result<()> {
+        let old = parse_turtle_to_graph(OLD)?;
+        let new = parse_turtle_to_graph(&OLD.replace("qudt:value 2", "qudt:value 3"))?;
+        let diff = diff_graphs(&old, &new)?;
+
+        assert_eq!((diff.added_count(), diff.removed_count()), (1, 1));
+        let changes = &diff.groups["cat:Well"];
+        assert_eq!(changes[0].subject, changes[1].subject);
+        assert_eq!(changes[0].change, Change::Removed);
+        assert!(changes[0].object.starts_with("\"2\""));
+        assert!(changes[1].object.starts_with("\"3\""));
+        Ok(())
+    }
+}
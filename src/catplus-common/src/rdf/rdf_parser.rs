@@ -1,7 +1,49 @@
 use anyhow::Result;
-use sophia::inmem::graph::LightGraph;
-use sophia_api::{parser::TripleParser, prelude::TripleSource};
-use sophia_turtle::parser::turtle::TurtleParser;
+use sophia::{inmem::graph::LightGraph, jsonld::JsonLdParser, xml::parser::RdfXmlParser};
+use sophia_api::{
+    parser::{QuadParser, TripleParser},
+    prelude::{MutableGraph, Quad, QuadSource, TripleSource},
+};
+use sophia_turtle::parser::{nt::NTriplesParser, turtle::TurtleParser};
+use std::path::Path;
+
+/// RDF syntaxes that can be parsed into a graph.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum RdfSyntax {
+    Turtle,
+    #[value(name = "ntriples")]
+    NTriples,
+    Jsonld,
+    #[value(name = "rdfxml")]
+    RdfXml,
+}
+
+impl RdfSyntax {
+    /// Guesses the syntax from the extension of a file path.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_lowercase();
+        match extension.as_str() {
+            "ttl" => Some(RdfSyntax::Turtle),
+            "nt" => Some(RdfSyntax::NTriples),
+            "jsonld" | "json" => Some(RdfSyntax::Jsonld),
+            "rdf" | "xml" | "owl" => Some(RdfSyntax::RdfXml),
+            _ => None,
+        }
+    }
+
+    /// Guesses the syntax from the start of the content.
+    /// Defaults to Turtle, which also covers N-Triples.
+    pub fn from_content(input: &str) -> Self {
+        let start = input.trim_start();
+        if start.starts_with('{') || start.starts_with('[') {
+            RdfSyntax::Jsonld
+        } else if ["<?xml", "<rdf:RDF", "<!--"].iter().any(|tag| start.starts_with(tag)) {
+            RdfSyntax::RdfXml
+        } else {
+            RdfSyntax::Turtle
+        }
+    }
+}
 
 /// Parses a Turtle string into an RDF graph.
 ///
@@ -22,3 +64,93 @@ pub fn parse_turtle_to_graph(turtle_input: &str) -> Result<LightGraph> {
 
     Ok(graph)
 }
+
+/// Parses a string in the given RDF syntax into an RDF graph.
+///
+/// If `format` is `None`, the syntax is guessed from the content.
+/// With JSON-LD, the triples of all named graphs are merged into the graph.
+///
+/// # Parameters
+/// - `input`: The RDF content as a string slice.
+/// - `format`: The syntax of the content, if known.
+///
+/// # Returns
+/// - `Result<LightGraph>`: The parsed RDF graph on success, or an error on failure.
+pub fn parse_to_graph(input: &str, format: Option<RdfSyntax>) -> Result<LightGraph> {
+    let format = format.unwrap_or_else(|| RdfSyntax::from_content(input));
+    let mut graph = LightGraph::new();
+
+    match format {
+        RdfSyntax::Turtle => return parse_turtle_to_graph(input),
+        RdfSyntax::NTriples => NTriplesParser {}
+            .parse_str(input)
+            .add_to_graph(&mut graph)
+            .map(|_| ())
+            .map_err(|e| anyhow::anyhow!("Failed to parse N-Triples input: {}", e))?,
+        RdfSyntax::Jsonld => JsonLdParser::new()
+            .parse_str(input)
+            .try_for_each_quad(|quad| {
+                let (spo, _) = quad.spog();
+                graph.insert_triple(spo).map(|_| ())
+            })
+            .map_err(|e| anyhow::anyhow!("Failed to parse JSON-LD input: {}", e))?,
+        RdfSyntax::RdfXml => RdfXmlParser::default()
+            .parse_str(input)
+            .add_to_graph(&mut graph)
+            .map(|_| ())
+            .map_err(|e| anyhow::anyhow!("Failed to parse RDF/XML input: {}", e))?,
+    }
+
+    Ok(graph)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sophia_api::prelude::Graph;
+
+    const TURTLE: &str = r#"
+        @prefix cat: <http://example.org/catplus/ontology/> .
+        <http://example.org/batch> a cat:Batch .
+    "#;
+
+    #[test]
+    fn test_guess_syntax() {
+        assert_eq!(RdfSyntax::from_path(Path::new("out.ttl")), Some(RdfSyntax::Turtle));
+        assert_eq!(RdfSyntax::from_path(Path::new("out.jsonld")), Some(RdfSyntax::Jsonld));
+        assert_eq!(RdfSyntax::from_path(Path::new("out.rdf")), Some(RdfSyntax::RdfXml));
+        assert_eq!(RdfSyntax::from_path(Path::new("-")), None);
+        assert_eq!(RdfSyntax::from_content(TURTLE), RdfSyntax::Turtle);
+        assert_eq!(RdfSyntax::from_content(" [{}]"), RdfSyntax::Jsonld);
+        assert_eq!(RdfSyntax::from_content("<?xml version=\"1.0\"?>"), RdfSyntax::RdfXml);
+    }
+
+    #[test]
+    fn test_parse_to_graph() -> Result<()> {
+        let jsonld = r#"[{
+            "@id": "http://example.org/batch",
+            "@type": ["http://example.org/catplus/ontology/Batch"]
+        }]"#;
+        let ntriples = "<http://example.org/batch> \
+            <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> \
+            <http://example.org/catplus/ontology/Batch> .";
+        let rdfxml = r#"<?xml version="1.0"?>
+            <rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+              <rdf:Description rdf:about="http://example.org/batch">
+                <rdf:type rdf:resource="http://example.org/catplus/ontology/Batch"/>
+              </rdf:Description>
+            </rdf:RDF>"#;
+
+        let expected = parse_turtle_to_graph(TURTLE)?;
+        for graph in [
+            parse_to_graph(jsonld, None)?,
+            parse_to_graph(ntriples, Some(RdfSyntax::NTriples))?,
+            parse_to_graph(rdfxml, None)?,
+        ] {
+            assert!(sophia_isomorphism::isomorphic_graphs(&expected, &graph)?);
+            assert_eq!(graph.triples().count(), 1);
+        }
+
+        Ok(())
+    }
+}
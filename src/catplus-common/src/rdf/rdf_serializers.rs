@@ -1,26 +1,39 @@
-use crate::graph::prefix_map::generate_prefix_map;
+use crate::graph::prefix_map::{generate_jsonld_context, PrefixEntry};
 use anyhow::{Context, Result};
+use serde_json::{json, Map, Value};
 use sophia::{
     api::{
+        ns::{rdf, xsd},
         prelude::*,
         serializer::{Stringifier, TripleSerializer},
+        term::TermKind,
     },
-    inmem::graph::LightGraph,
+    inmem::{dataset::LightDataset, graph::LightGraph},
     jsonld::{serializer::JsonLdSerializer, JsonLdOptions},
 };
-use sophia_turtle::serializer::turtle::{TurtleConfig, TurtleSerializer};
+use sophia_c14n::rdfc10;
+use sophia_turtle::serializer::{
+    nq::NqSerializer,
+    nt::NtSerializer,
+    trig::{TrigConfig, TrigSerializer},
+    turtle::{TurtleConfig, TurtleSerializer},
+};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Write,
+};
 
 /// Serialize an RDF graph to Turtle format
 ///
 /// # Parameters
 /// - `graph`: A reference to the graph to be serialized.
+/// - `prefix_map`: The prefixes to abbreviate IRIs with, see
+///   [IriBase::prefix_map](crate::graph::iri_base::IriBase::prefix_map).
 ///
 /// # Returns
 /// A `Result` containing the Turtle serialization as a `String`, or an error if serialization fails.
-pub fn serialize_graph_to_turtle(graph: &LightGraph) -> Result<String> {
-    let prefix_map = generate_prefix_map();
-
-    let config = TurtleConfig::default().with_pretty(true).with_own_prefix_map(prefix_map);
+pub fn serialize_graph_to_turtle(graph: &LightGraph, prefix_map: &[PrefixEntry]) -> Result<String> {
+    let config = TurtleConfig::default().with_pretty(true).with_own_prefix_map(prefix_map.to_vec());
 
     let mut serializer = TurtleSerializer::new_stringifier_with_config(config);
 
@@ -48,3 +61,439 @@ pub fn serialize_graph_to_jsonld(graph: &LightGraph) -> Result<String> {
     // Extract the JSON-LD string from the serializer
     Ok(serializer.as_str().to_string())
 }
+
+/// Serialize an RDF graph to compacted JSON-LD format
+///
+/// IRIs are compacted with an `@context` built from `prefix_map`.
+/// If `frame_type` is given, the nodes of that type are the roots of the `@graph`
+/// and the nodes they reference are embedded in them, once.
+///
+/// # Parameters
+/// - `graph`: A reference to the graph to be serialized.
+/// - `prefix_map`: The prefixes to abbreviate IRIs with, see
+///   [IriBase::prefix_map](crate::graph::iri_base::IriBase::prefix_map).
+/// - `context_url`: URL of the context to reference, or `None` to embed the context inline.
+/// - `frame_type`: IRI of the type of the root nodes, or `None` to list all nodes.
+///
+/// # Returns
+/// A `Result` containing the JSON-LD serialization as a `String`, or an error if serialization fails.
+pub fn serialize_graph_to_compact_jsonld(
+    graph: &LightGraph,
+    prefix_map: &[PrefixEntry],
+    context_url: Option<&str>,
+    frame_type: Option<&str>,
+) -> Result<String> {
+    let namespaces: Vec<(String, String)> = prefix_map
+        .iter()
+        .map(|(prefix, iri)| (prefix.as_str().to_string(), iri.as_str().to_string()))
+        .collect();
+    let compact = |iri: &str| compact_iri(iri, &namespaces);
+
+    // Node objects by identifier, in order of first appearance.
+    let mut order: Vec<String> = Vec::new();
+    let mut nodes: HashMap<String, Map<String, Value>> = HashMap::new();
+    let mut references: HashMap<String, usize> = HashMap::new();
+
+    for triple in graph.triples() {
+        let [subject, predicate, object] = triple?;
+        let id = jsonld_node_id(&subject, &compact)?;
+        let node = nodes.entry(id.clone()).or_insert_with(|| {
+            order.push(id.clone());
+            Map::from_iter([("@id".to_string(), Value::String(id))])
+        });
+
+        let (key, value) = if Term::eq(&predicate, rdf::type_) && object.is_iri() {
+            ("@type".to_string(), Value::String(compact(&object.iri().unwrap())))
+        } else {
+            let predicate_iri =
+                predicate.iri().ok_or_else(|| anyhow::anyhow!("Predicate must be an IRI"))?;
+            (compact(&predicate_iri), jsonld_value(&object, &compact)?)
+        };
+        if object.is_iri() || object.is_blank_node() {
+            *references.entry(jsonld_node_id(&object, &compact)?).or_default() += 1;
+        }
+
+        match node.get_mut(&key) {
+            None => {
+                node.insert(key, value);
+            }
+            Some(Value::Array(values)) => values.push(value),
+            Some(existing) => *existing = Value::Array(vec![existing.take(), value]),
+        }
+    }
+
+    let graph_nodes: Vec<Value> = match frame_type {
+        None => order.iter().map(|id| Value::Object(nodes[id].clone())).collect(),
+        Some(frame_type) => {
+            let frame_type = Value::String(compact(frame_type));
+            let roots: Vec<&String> = order
+                .iter()
+                .filter(|id| match nodes[*id].get("@type") {
+                    Some(Value::Array(types)) => types.contains(&frame_type),
+                    Some(node_type) => *node_type == frame_type,
+                    None => false,
+                })
+                .collect();
+
+            // Roots are never embedded in other nodes.
+            let mut embedded: HashSet<String> = roots.iter().map(|id| id.to_string()).collect();
+            roots
+                .iter()
+                .map(|id| embed_jsonld_node(id, &nodes, &references, &mut embedded))
+                .collect()
+        }
+    };
+
+    let context = match context_url {
+        Some(url) => Value::String(url.to_string()),
+        None => generate_jsonld_context(prefix_map),
+    };
+    let document = json!({ "@context": context, "@graph": graph_nodes });
+
+    serde_json::to_string_pretty(&document).context("Failed to serialize graph to JSON-LD")
+}
+
+/// Serialize an RDF graph to N-Triples format
+///
+/// # Parameters
+/// - `graph`: A reference to the graph to be serialized.
+///
+/// # Returns
+/// A `Result` containing the N-Triples serialization as a `String`, or an error if serialization fails.
+pub fn serialize_graph_to_ntriples(graph: &LightGraph) -> Result<String> {
+    let mut serializer = NtSerializer::new_stringifier();
+
+    serializer.serialize_graph(graph).context("Failed to serialize graph to N-Triples")?;
+
+    Ok(serializer.as_str().to_string())
+}
+
+/// Serialize an RDF graph to N-Quads format
+///
+/// # Parameters
+/// - `graph`: A reference to the graph to be serialized.
+/// - `graph_name`: IRI of the named graph holding the triples, or `None` for the default graph.
+///
+/// # Returns
+/// A `Result` containing the N-Quads serialization as a `String`, or an error if serialization fails.
+pub fn serialize_graph_to_nquads(graph: &LightGraph, graph_name: Option<&str>) -> Result<String> {
+    serialize_graphs_to_nquads(&[(graph, graph_name.map(str::to_string))])
+}
+
+/// Serialize several RDF graphs to N-Quads format, as a single dataset
+///
+/// # Parameters
+/// - `graphs`: The graphs with the IRI of their named graph, or `None` for the default graph.
+///
+/// # Returns
+/// A `Result` containing the N-Quads serialization as a `String`, or an error if serialization fails.
+pub fn serialize_graphs_to_nquads(graphs: &[(&LightGraph, Option<String>)]) -> Result<String> {
+    let dataset = graphs_to_dataset(graphs)?;
+
+    let mut serializer = NqSerializer::new_stringifier();
+    serializer.serialize_dataset(&dataset).context("Failed to serialize graph to N-Quads")?;
+
+    Ok(serializer.as_str().to_string())
+}
+
+/// Serialize an RDF graph to canonical N-Quads, following RDF Dataset Canonicalization (RDFC-1.0)
+///
+/// Blank nodes are relabeled canonically and the quads are sorted, so that isomorphic
+/// graphs give identical output. The triples are placed in the default graph.
+///
+/// # Parameters
+/// - `graph`: A reference to the graph to be serialized.
+///
+/// # Returns
+/// A `Result` containing the canonical N-Quads as a `String`, or an error if serialization fails.
+pub fn serialize_graph_to_canonical_nquads(graph: &LightGraph) -> Result<String> {
+    let dataset = graphs_to_dataset(&[(graph, None)])?;
+
+    let mut output = Vec::new();
+    rdfc10::normalize(&dataset, &mut output).context("Failed to canonicalize graph")?;
+
+    String::from_utf8(output).context("Canonical N-Quads are not valid UTF-8")
+}
+
+/// Serialize an RDF graph to TriG format
+///
+/// # Parameters
+/// - `graph`: A reference to the graph to be serialized.
+/// - `graph_name`: IRI of the named graph holding the triples, or `None` for the default graph.
+/// - `prefix_map`: The prefixes to abbreviate IRIs with, see
+///   [IriBase::prefix_map](crate::graph::iri_base::IriBase::prefix_map).
+///
+/// # Returns
+/// A `Result` containing the TriG serialization as a `String`, or an error if serialization fails.
+pub fn serialize_graph_to_trig(
+    graph: &LightGraph,
+    graph_name: Option<&str>,
+    prefix_map: &[PrefixEntry],
+) -> Result<String> {
+    serialize_graphs_to_trig(&[(graph, graph_name.map(str::to_string))], prefix_map)
+}
+
+/// Serialize several RDF graphs to TriG format, as a single dataset
+///
+/// # Parameters
+/// - `graphs`: The graphs with the IRI of their named graph, or `None` for the default graph.
+/// - `prefix_map`: The prefixes to abbreviate IRIs with, see
+///   [IriBase::prefix_map](crate::graph::iri_base::IriBase::prefix_map).
+///
+/// # Returns
+/// A `Result` containing the TriG serialization as a `String`, or an error if serialization fails.
+pub fn serialize_graphs_to_trig(
+    graphs: &[(&LightGraph, Option<String>)],
+    prefix_map: &[PrefixEntry],
+) -> Result<String> {
+    let dataset = graphs_to_dataset(graphs)?;
+
+    let config = TrigConfig::default().with_pretty(true).with_own_prefix_map(prefix_map.to_vec());
+
+    let mut serializer = TrigSerializer::new_stringifier_with_config(config);
+
+    serializer.serialize_dataset(&dataset).context("Failed to serialize graph to TriG")?;
+
+    Ok(serializer.as_str().to_string())
+}
+
+/// Serialize an RDF graph to RDF/XML format
+///
+/// The namespaces of `prefix_map` are declared on the root element, so that
+/// the prefixes match the Turtle serialization. Predicates outside of these namespaces
+/// get generated `ns<N>` prefixes.
+///
+/// # Parameters
+/// - `graph`: A reference to the graph to be serialized.
+/// - `prefix_map`: The prefixes to abbreviate IRIs with, see
+///   [IriBase::prefix_map](crate::graph::iri_base::IriBase::prefix_map).
+///
+/// # Returns
+/// A `Result` containing the RDF/XML serialization as a `String`, or an error if serialization fails.
+pub fn serialize_graph_to_rdfxml(graph: &LightGraph, prefix_map: &[PrefixEntry]) -> Result<String> {
+    let mut namespaces: Vec<(String, String)> = prefix_map
+        .iter()
+        .map(|(prefix, iri)| (prefix.as_str().to_string(), iri.as_str().to_string()))
+        .collect();
+
+    // Descriptions are grouped by subject, in order of first appearance.
+    let mut descriptions: Vec<(String, String)> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+
+    for triple in graph.triples() {
+        let [subject, predicate, object] = triple?;
+
+        let subject_attribute = match subject.kind() {
+            TermKind::Iri => format!("rdf:about=\"{}\"", escape_xml(&subject.iri().unwrap())),
+            TermKind::BlankNode => {
+                format!("rdf:nodeID=\"b{}\"", subject.bnode_id().unwrap().as_str())
+            }
+            _ => return Err(anyhow::anyhow!("Unsupported subject in RDF/XML: {:?}", subject)),
+        };
+        let predicate_iri =
+            predicate.iri().ok_or_else(|| anyhow::anyhow!("Predicate must be an IRI"))?;
+        let qname = rdfxml_qname(&predicate_iri, &mut namespaces)?;
+
+        let mut property = String::new();
+        match object.kind() {
+            TermKind::Iri => write!(
+                property,
+                "<{} rdf:resource=\"{}\"/>",
+                qname,
+                escape_xml(&object.iri().unwrap())
+            )?,
+            TermKind::BlankNode => write!(
+                property,
+                "<{} rdf:nodeID=\"b{}\"/>",
+                qname,
+                object.bnode_id().unwrap().as_str()
+            )?,
+            TermKind::Literal => {
+                let lexical_form = escape_xml(&object.lexical_form().unwrap());
+                if let Some(tag) = object.language_tag() {
+                    write!(
+                        property,
+                        "<{} xml:lang=\"{}\">{}</{}>",
+                        qname,
+                        tag.as_str(),
+                        lexical_form,
+                        qname
+                    )?
+                } else {
+                    let datatype = object.datatype().unwrap();
+                    if Term::eq(&datatype, xsd::string) {
+                        write!(property, "<{}>{}</{}>", qname, lexical_form, qname)?
+                    } else {
+                        write!(
+                            property,
+                            "<{} rdf:datatype=\"{}\">{}</{}>",
+                            qname,
+                            escape_xml(&datatype),
+                            lexical_form,
+                            qname
+                        )?
+                    }
+                }
+            }
+            _ => return Err(anyhow::anyhow!("Unsupported object in RDF/XML: {:?}", object)),
+        }
+
+        let position = *index.entry(subject_attribute.clone()).or_insert_with(|| {
+            descriptions.push((subject_attribute, String::new()));
+            descriptions.len() - 1
+        });
+        writeln!(descriptions[position].1, "    {}", property)?;
+    }
+
+    let mut output = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<rdf:RDF");
+    for (prefix, iri) in &namespaces {
+        write!(output, "\n  xmlns:{}=\"{}\"", prefix, escape_xml(iri))?;
+    }
+    output.push_str(">\n");
+    for (subject_attribute, properties) in descriptions {
+        write!(
+            output,
+            "  <rdf:Description {}>\n{}  </rdf:Description>\n",
+            subject_attribute, properties
+        )?;
+    }
+    output.push_str("</rdf:RDF>\n");
+
+    Ok(output)
+}
+
+/// Compacts an IRI to `prefix:suffix` with the longest matching namespace.
+/// IRIs without a matching namespace are returned as is.
+pub(crate) fn compact_iri(iri: &str, namespaces: &[(String, String)]) -> String {
+    namespaces
+        .iter()
+        .filter_map(|(prefix, namespace)| {
+            iri.strip_prefix(namespace.as_str())
+                .filter(|suffix| !suffix.is_empty() && !suffix.starts_with("//"))
+                .map(|suffix| (namespace.len(), format!("{}:{}", prefix, suffix)))
+        })
+        .max_by_key(|(length, _)| *length)
+        .map(|(_, compacted)| compacted)
+        .unwrap_or_else(|| iri.to_string())
+}
+
+/// Returns the `@id` of a node: a compacted IRI or a blank node identifier.
+fn jsonld_node_id<T: Term>(term: &T, compact: &impl Fn(&str) -> String) -> Result<String> {
+    match term.kind() {
+        TermKind::Iri => Ok(compact(&term.iri().unwrap())),
+        TermKind::BlankNode => Ok(format!("_:{}", term.bnode_id().unwrap().as_str())),
+        _ => Err(anyhow::anyhow!("Node must be an IRI or a blank node")),
+    }
+}
+
+/// Returns the compacted JSON-LD value of an object term.
+fn jsonld_value<T: Term>(term: &T, compact: &impl Fn(&str) -> String) -> Result<Value> {
+    if term.kind() != TermKind::Literal {
+        return Ok(json!({ "@id": jsonld_node_id(term, compact)? }));
+    }
+
+    let lexical_form = term.lexical_form().unwrap().to_string();
+    if let Some(tag) = term.language_tag() {
+        return Ok(json!({ "@value": lexical_form, "@language": tag.as_str() }));
+    }
+    let datatype = term.datatype().unwrap();
+    if Term::eq(&datatype, xsd::string) {
+        Ok(Value::String(lexical_form))
+    } else {
+        Ok(json!({ "@value": lexical_form, "@type": compact(&datatype) }))
+    }
+}
+
+/// Returns the node object `id`, with the nodes it references embedded if not yet embedded.
+/// Blank nodes that are referenced only once lose their `@id`.
+fn embed_jsonld_node(
+    id: &str,
+    nodes: &HashMap<String, Map<String, Value>>,
+    references: &HashMap<String, usize>,
+    embedded: &mut HashSet<String>,
+) -> Value {
+    embedded.insert(id.to_string());
+    let mut node = nodes[id].clone();
+
+    for (key, value) in node.iter_mut() {
+        if key.starts_with('@') {
+            continue;
+        }
+        let values = match value {
+            Value::Array(values) => values.iter_mut().collect(),
+            value => vec![value],
+        };
+        for value in values {
+            let reference = match value.as_object() {
+                Some(object) if object.len() == 1 => object.get("@id").and_then(Value::as_str),
+                _ => None,
+            };
+            if let Some(reference) = reference {
+                if nodes.contains_key(reference) && !embedded.contains(reference) {
+                    *value = embed_jsonld_node(reference, nodes, references, embedded);
+                }
+            }
+        }
+    }
+
+    if id.starts_with("_:") && references.get(id).copied().unwrap_or(0) <= 1 {
+        node.remove("@id");
+    }
+    Value::Object(node)
+}
+
+/// Builds a dataset holding each graph in its named graph, or in the default graph.
+fn graphs_to_dataset(graphs: &[(&LightGraph, Option<String>)]) -> Result<LightDataset> {
+    let mut dataset = LightDataset::new();
+    for (graph, graph_name) in graphs {
+        let graph_name = graph_name
+            .as_ref()
+            .map(|name| IriRef::new(name.to_owned()))
+            .transpose()
+            .context("Invalid graph name IRI")?;
+
+        for triple in graph.triples() {
+            let [s, p, o] = triple?;
+            dataset.insert(s, p, o, graph_name.as_ref())?;
+        }
+    }
+
+    Ok(dataset)
+}
+
+/// Returns the qualified name of a predicate IRI for RDF/XML.
+/// The longest known namespace is used, otherwise a new `ns<N>` namespace is added.
+fn rdfxml_qname(iri: &str, namespaces: &mut Vec<(String, String)>) -> Result<String> {
+    let known = namespaces
+        .iter()
+        .filter(|(_, namespace)| {
+            iri.strip_prefix(namespace.as_str()).is_some_and(is_xml_local_name)
+        })
+        .max_by_key(|(_, namespace)| namespace.len());
+    if let Some((prefix, namespace)) = known {
+        return Ok(format!("{}:{}", prefix, &iri[namespace.len()..]));
+    }
+
+    let split = iri
+        .char_indices()
+        .filter(|(_, c)| *c == '#' || *c == '/')
+        .map(|(i, _)| i + 1)
+        .find(|i| is_xml_local_name(&iri[*i..]))
+        .ok_or_else(|| anyhow::anyhow!("Cannot serialize predicate <{}> in RDF/XML", iri))?;
+    let prefix = format!("ns{}", namespaces.len());
+    namespaces.push((prefix.clone(), iri[..split].to_string()));
+
+    Ok(format!("{}:{}", prefix, &iri[split..]))
+}
+
+/// Checks that `name` can be used as the local part of an XML element name.
+fn is_xml_local_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_' || c == '-' || c == '.')
+}
+
+/// Escapes the special characters of XML text and attribute values.
+fn escape_xml(value: &str) -> String {
+    value.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
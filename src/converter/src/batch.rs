@@ -0,0 +1,462 @@
+use crate::{
+    convert::{json_str_to_graph, output_file_uri, serialize_graph, ConverterConfig},
+    io::{
+        determine_input_action, read_to_string, save_output, DetectionRule, InputAction, InputType,
+    },
+    validate::{nonconformance, save_validation_report, OnInvalid, QUARANTINE_FOLDER},
+};
+use anyhow::{Context, Result};
+use catplus_common::{
+    graph::{graph_builder::GraphBuilder, utils::sha256_hex},
+    models::{
+        agilent::LiquidChromatographyAggregateDocumentWrapper, bravo::BravoActionWrapper,
+        hci::CampaignWrapper, synth::SynthBatch,
+    },
+};
+use sophia_api::graph::Graph;
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Outcome of processing a single input file.
+#[derive(Debug)]
+pub enum Outcome {
+    /// The file was converted and written to the output path.
+    Converted { output_path: PathBuf, triple_count: usize },
+    /// The file was not converted, with the reason.
+    Skipped(String),
+    /// The file was not converted, since its output from the same content and options exists.
+    UpToDate { output_path: PathBuf },
+    /// The file was converted, but its graph does not conform to the shapes.
+    /// The output is written with [OnInvalid::Warn], or quarantined next to its
+    /// validation report with [OnInvalid::Quarantine].
+    Invalid {
+        output_path: PathBuf,
+        triple_count: usize,
+        report_path: Option<PathBuf>,
+        /// Counts of the validation results and first message.
+        reason: String,
+    },
+    /// The conversion failed.
+    Failed(anyhow::Error),
+}
+
+/// The result of processing a single input file.
+#[derive(Debug)]
+pub struct FileResult {
+    pub input_path: PathBuf,
+    pub input_type: Option<InputType>,
+    pub rule: Option<DetectionRule>,
+    /// Hex-encoded SHA-256 digest of the content the file was converted from.
+    pub input_sha256: Option<String>,
+    pub outcome: Outcome,
+    pub elapsed: Duration,
+}
+
+impl FileResult {
+    pub fn is_failed(&self) -> bool {
+        matches!(self.outcome, Outcome::Failed(_))
+    }
+
+    /// Prints the log lines for this file.
+    pub fn log(&self) {
+        let input_path = self.input_path.display();
+        if let (Some(input_type), Some(rule)) = (self.input_type, self.rule) {
+            println!("Detected {:?} input for '{}' ({})", input_type, input_path, rule);
+        }
+        match &self.outcome {
+            Outcome::Converted { output_path, .. } => {
+                println!("Processed '{}' -> '{}'", input_path, output_path.display())
+            }
+            Outcome::Skipped(reason) => println!("Skipping file '{}': {}", input_path, reason),
+            Outcome::UpToDate { output_path } => {
+                println!("Up to date '{}' -> '{}'", input_path, output_path.display())
+            }
+            Outcome::Invalid { output_path, reason, .. } => {
+                eprintln!("Invalid '{}' -> '{}': {}", input_path, output_path.display(), reason)
+            }
+            Outcome::Failed(err) => eprintln!("Failed to process '{}': {:#}", input_path, err),
+        }
+    }
+}
+
+/// Converts the file at `config.input_path` and saves the output in `output_folder`.
+///
+/// Errors are not propagated but recorded as [Outcome::Failed] in the returned result.
+pub fn process_file(
+    config: &ConverterConfig,
+    output_folder: &Path,
+    input_type: Option<InputType>,
+) -> FileResult {
+    let start = Instant::now();
+    let input_path = config.input_path.clone();
+    let (input_type, rule, input_sha256, outcome) =
+        match determine_input_action(&input_path, input_type) {
+            Ok(InputAction::Skip(reason)) => (None, None, None, Outcome::Skipped(reason)),
+            Ok(InputAction::Process(input_type, rule, content)) => {
+                let input_sha256 = sha256_hex(content.as_bytes());
+                let outcome = convert_file(config, output_folder, input_type, &content)
+                    .unwrap_or_else(Outcome::Failed);
+                (Some(input_type), Some(rule), Some(input_sha256), outcome)
+            }
+            Err(err) => (None, None, None, Outcome::Failed(err)),
+        };
+
+    FileResult { input_path, input_type, rule, input_sha256, outcome, elapsed: start.elapsed() }
+}
+
+/// Processes the `(input file, output folder)` pairs of `inputs` with `jobs` worker threads.
+///
+/// Results are returned, and passed to `on_result` as they complete, in the order of `inputs`.
+/// Unless `keep_going` is set, no new files are started after the first failure, and
+/// the files that were already being converted are completed and reported.
+pub fn process_files<F>(
+    inputs: &[(PathBuf, PathBuf)],
+    config: &ConverterConfig,
+    input_type: Option<InputType>,
+    jobs: usize,
+    keep_going: bool,
+    mut on_result: F,
+) -> Vec<FileResult>
+where
+    F: FnMut(&FileResult),
+{
+    run_in_order(
+        inputs.len(),
+        jobs,
+        keep_going,
+        |index| {
+            let (input_path, output_folder) = &inputs[index];
+            let mut file_config = config.clone();
+            file_config.input_path = input_path.clone();
+            (process_file(&file_config, output_folder, input_type), ())
+        },
+        |result, _| on_result(result),
+    )
+}
+
+/// Converts `input_paths` with `jobs` worker threads and merges their graphs into one.
+///
+/// The graphs are merged in the order of `input_paths`, so that the output is deterministic.
+/// Converted files are reported with `output_path`, where the merged graph is to be written.
+/// The merged provenance graph is named after the URI of `output_path`.
+/// Failures stop the merge as in [process_files], unless `keep_going` is set.
+pub fn merge_files<F>(
+    input_paths: &[PathBuf],
+    config: &ConverterConfig,
+    input_type: Option<InputType>,
+    jobs: usize,
+    keep_going: bool,
+    output_path: &Path,
+    mut on_result: F,
+) -> Result<(GraphBuilder, Vec<FileResult>)>
+where
+    F: FnMut(&FileResult),
+{
+    let mut merged = GraphBuilder::new();
+    merged.rebase(config.iri_base.clone())?;
+    merged.provenance_graph_name =
+        Some(format!("{}#provenance", output_file_uri(config, output_path)?));
+    let mut merge_error = None;
+    let results = run_in_order(
+        input_paths.len(),
+        jobs,
+        keep_going,
+        |index| {
+            let mut file_config = config.clone();
+            file_config.input_path = input_paths[index].clone();
+            build_file_graph(&file_config, input_type, output_path)
+        },
+        |result, graph_builder| {
+            if let (Some(graph_builder), None) = (graph_builder, &merge_error) {
+                merge_error = merged
+                    .merge(&graph_builder)
+                    .with_context(|| format!("Failed to merge '{}'", result.input_path.display()))
+                    .err();
+            }
+            on_result(result);
+        },
+    );
+
+    match merge_error {
+        Some(err) => Err(err),
+        None => Ok((merged, results)),
+    }
+}
+
+/// Builds the graph of the file at `config.input_path` for [merge_files].
+fn build_file_graph(
+    config: &ConverterConfig,
+    input_type: Option<InputType>,
+    output_path: &Path,
+) -> (FileResult, Option<GraphBuilder>) {
+    let start = Instant::now();
+    let input_path = config.input_path.clone();
+    let (input_type, rule, input_sha256, outcome, graph_builder) =
+        match determine_input_action(&input_path, input_type) {
+            Ok(InputAction::Skip(reason)) => (None, None, None, Outcome::Skipped(reason), None),
+            Ok(InputAction::Process(input_type, rule, content)) => {
+                let input_sha256 = Some(sha256_hex(content.as_bytes()));
+                match build_graph_from_str(&content, config, input_type)
+                    .with_context(|| format!("Failed to convert '{}' to RDF", input_path.display()))
+                {
+                    Ok(graph_builder) => {
+                        let output_path = output_path.to_path_buf();
+                        let triple_count = graph_builder.graph.triples().count();
+                        let outcome = Outcome::Converted { output_path, triple_count };
+                        (Some(input_type), Some(rule), input_sha256, outcome, Some(graph_builder))
+                    }
+                    Err(err) => {
+                        (Some(input_type), Some(rule), input_sha256, Outcome::Failed(err), None)
+                    }
+                }
+            }
+            Err(err) => (None, None, None, Outcome::Failed(err), None),
+        };
+
+    let result = FileResult {
+        input_path,
+        input_type,
+        rule,
+        input_sha256,
+        outcome,
+        elapsed: start.elapsed(),
+    };
+    (result, graph_builder)
+}
+
+/// Runs `work` on the indices `0..count` with `jobs` worker threads.
+///
+/// The results of `work` are returned, and passed to `on_result` with their payload as
+/// they complete, in the order of the indices. Unless `keep_going` is set, no new work is
+/// started after the first failure, and the work already started is completed and reported,
+/// so that every written output appears in the results.
+pub(crate) fn run_in_order<T, W, F>(
+    count: usize,
+    jobs: usize,
+    keep_going: bool,
+    work: W,
+    mut on_result: F,
+) -> Vec<FileResult>
+where
+    T: Send,
+    W: Fn(usize) -> (FileResult, T) + Sync,
+    F: FnMut(&FileResult, T),
+{
+    let next = AtomicUsize::new(0);
+    let stop = AtomicBool::new(false);
+    let mut results = Vec::with_capacity(count);
+
+    thread::scope(|scope| {
+        let (sender, receiver) = mpsc::channel();
+        for _ in 0..jobs.clamp(1, count.max(1)) {
+            let sender = sender.clone();
+            let (next, stop, work) = (&next, &stop, &work);
+            scope.spawn(move || {
+                while !stop.load(Ordering::Relaxed) {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    if index >= count {
+                        break;
+                    }
+                    let output = work(index);
+                    if output.0.is_failed() && !keep_going {
+                        stop.store(true, Ordering::Relaxed);
+                    }
+                    if sender.send((index, output)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(sender);
+
+        // Reorder the results as they complete to follow the order of the inputs.
+        // The started indices are contiguous, so all of them are eventually reported.
+        let mut pending = BTreeMap::new();
+        for (index, output) in receiver {
+            pending.insert(index, output);
+            while let Some((result, payload)) = pending.remove(&results.len()) {
+                on_result(&result, payload);
+                results.push(result);
+            }
+        }
+    });
+
+    results
+}
+
+/// Builds the RDF graph of a file using the model of its input type.
+pub fn build_graph(config: &ConverterConfig, input_type: InputType) -> Result<GraphBuilder> {
+    let input_content = read_to_string(&config.input_path)?;
+    build_graph_from_str(&input_content, config, input_type)
+}
+
+/// Builds the RDF graph of JSON content using the model of its input type.
+///
+/// The overrides of the input type in `config` are applied.
+pub fn build_graph_from_str(
+    input_content: &str,
+    config: &ConverterConfig,
+    input_type: InputType,
+) -> Result<GraphBuilder> {
+    let config = &config.for_input_type(input_type);
+    match input_type {
+        InputType::HCI => {
+            json_str_to_graph::<CampaignWrapper>(input_content, config, Some(input_type))
+        }
+        InputType::Synth => {
+            json_str_to_graph::<SynthBatch>(input_content, config, Some(input_type))
+        }
+        InputType::Agilent => json_str_to_graph::<LiquidChromatographyAggregateDocumentWrapper>(
+            input_content,
+            config,
+            Some(input_type),
+        ),
+        InputType::Bravo => {
+            json_str_to_graph::<BravoActionWrapper>(input_content, config, Some(input_type))
+        }
+    }
+}
+
+/// Converts the `content` of a file of a known input type, with the overrides of that type.
+///
+/// With validation, the graph is validated before it is written and non-conforming graphs
+/// are handled with the [OnInvalid] policy.
+fn convert_file(
+    config: &ConverterConfig,
+    output_folder: &Path,
+    input_type: InputType,
+    content: &str,
+) -> Result<Outcome> {
+    let config = &config.for_input_type(input_type);
+    let input_path = &config.input_path;
+    let error_context = || {
+        format!("Failed to convert '{}' to RDF format '{:?}'", input_path.display(), config.format)
+    };
+    let graph_builder =
+        build_graph_from_str(content, config, input_type).with_context(error_context)?;
+    let report = match &config.validation {
+        Some(validation) => Some(
+            validation
+                .validate(&graph_builder.graph)
+                .with_context(|| format!("Failed to validate '{}'", input_path.display()))?,
+        )
+        .filter(|report| !report.conforms),
+        None => None,
+    };
+    let on_invalid = config.validation.as_ref().map(|validation| validation.on_invalid);
+    if let (Some(report), Some(OnInvalid::Fail)) = (&report, on_invalid) {
+        anyhow::bail!(nonconformance(report));
+    }
+    let serialized_graph = serialize_graph(&graph_builder, config).with_context(error_context)?;
+    let triple_count = graph_builder.graph.triples().count();
+
+    let output_folder = match on_invalid {
+        Some(OnInvalid::Quarantine) if report.is_some() => output_folder.join(QUARANTINE_FOLDER),
+        _ => output_folder.to_path_buf(),
+    };
+    fs::create_dir_all(&output_folder).with_context(|| {
+        format!("Failed to create output folder '{}'.", output_folder.display())
+    })?;
+    let output_path = save_output(input_path, &output_folder, &serialized_graph, &config.format)?;
+    let Some(report) = report else {
+        return Ok(Outcome::Converted { output_path, triple_count });
+    };
+    let report_path = match on_invalid {
+        Some(OnInvalid::Quarantine) => {
+            Some(save_validation_report(&report, &graph_builder.graph, &output_path)?)
+        }
+        _ => None,
+    };
+    Ok(Outcome::Invalid { output_path, triple_count, report_path, reason: nonconformance(&report) })
+}
+
+/// Prints a table with the outcome of every processed file, followed by the totals.
+pub fn print_summary(results: &[FileResult]) {
+    let width = results
+        .iter()
+        .map(|result| result.input_path.display().to_string().len())
+        .max()
+        .unwrap_or(0)
+        .max("FILE".len());
+
+    println!("\n{:<10}  {:<width$}  DETAILS", "STATUS", "FILE");
+    for result in results {
+        let (status, details) = match &result.outcome {
+            Outcome::Converted { output_path, .. } => {
+                ("converted", format!("-> {}", output_path.display()))
+            }
+            Outcome::Skipped(reason) => ("skipped", reason.clone()),
+            Outcome::UpToDate { output_path } => {
+                ("up-to-date", format!("-> {}", output_path.display()))
+            }
+            Outcome::Invalid { output_path, reason, .. } => {
+                ("invalid", format!("-> {}: {}", output_path.display(), reason))
+            }
+            Outcome::Failed(err) => ("failed", format!("{:#}", err)),
+        };
+        println!(
+            "{:<10}  {:<width$}  {}",
+            status,
+            result.input_path.display().to_string(),
+            details
+        );
+    }
+
+    let converted =
+        results.iter().filter(|r| matches!(r.outcome, Outcome::Converted { .. })).count();
+    let skipped = results.iter().filter(|r| matches!(r.outcome, Outcome::Skipped(_))).count();
+    let up_to_date =
+        results.iter().filter(|r| matches!(r.outcome, Outcome::UpToDate { .. })).count();
+    let invalid = results.iter().filter(|r| matches!(r.outcome, Outcome::Invalid { .. })).count();
+    let failed = results.iter().filter(|r| r.is_failed()).count();
+    let mut totals = vec![format!("{} converted", converted)];
+    if invalid > 0 {
+        totals.push(format!("{} invalid", invalid));
+    }
+    if up_to_date > 0 {
+        totals.push(format!("{} up to date", up_to_date));
+    }
+    totals.push(format!("{} skipped", skipped));
+    totals.push(format!("{} failed", failed));
+    println!("\n{}.", totals.join(", "));
+}
+
+/// Prints the number of converted, skipped and failed files per input folder.
+pub fn print_folder_counts(results: &[FileResult]) {
+    let mut counts: BTreeMap<&Path, [usize; 5]> = BTreeMap::new();
+    for result in results {
+        let folder = result.input_path.parent().unwrap_or(Path::new(""));
+        let folder_counts = counts.entry(folder).or_default();
+        match result.outcome {
+            Outcome::Converted { .. } => folder_counts[0] += 1,
+            Outcome::Skipped(_) => folder_counts[1] += 1,
+            Outcome::Failed(_) => folder_counts[2] += 1,
+            Outcome::UpToDate { .. } => folder_counts[3] += 1,
+            Outcome::Invalid { .. } => folder_counts[4] += 1,
+        }
+    }
+
+    for (folder, [converted, skipped, failed, up_to_date, invalid]) in counts {
+        let invalid = if invalid > 0 { format!(" {} invalid,", invalid) } else { String::new() };
+        let up_to_date =
+            if up_to_date > 0 { format!(" {} up to date,", up_to_date) } else { String::new() };
+        println!(
+            "'{}': {} converted,{}{} {} skipped, {} failed",
+            folder.display(),
+            converted,
+            invalid,
+            up_to_date,
+            skipped,
+            failed
+        );
+    }
+}
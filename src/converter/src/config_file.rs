@@ -0,0 +1,125 @@
+use crate::{
+    convert::{InputTypeOverrides, RdfFormat},
+    io::InputType,
+};
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Configuration file looked up in the working directory if no path is given.
+pub const DEFAULT_CONFIG_FILE: &str = "catplus.toml";
+
+/// Profile used if none is selected.
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Converter settings with named profiles, read from a TOML file such as:
+///
+/// ```toml
+/// [profiles.default]
+/// format = "turtle"
+/// output-folder = "rdf"
+/// prefix = "https://data.example.org/raw/"
+/// materialize = true
+///
+/// [profiles.default.input-types.agilent]
+/// format = "trig"
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigFile {
+    #[serde(default)]
+    pub profiles: HashMap<String, Profile>,
+}
+
+/// Converter options of a profile. The command line flags take precedence over them.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Profile {
+    pub format: Option<RdfFormat>,
+    /// Relative paths are resolved against the folder of the configuration file.
+    pub output_folder: Option<PathBuf>,
+    pub prefix: Option<String>,
+    pub resource_base: Option<String>,
+    pub ontology_iri: Option<String>,
+    pub materialize: Option<bool>,
+    /// Options of specific input types, by name as in `--input-type`.
+    #[serde(default)]
+    pub input_types: HashMap<String, InputTypeOverrides>,
+}
+
+impl ConfigFile {
+    /// Parses the TOML content of a configuration file.
+    pub fn parse(content: &str) -> Result<Self> {
+        toml::from_str(content).context("Invalid configuration file")
+    }
+
+    /// Reads the configuration file at `path`.
+    ///
+    /// Relative output folders of the profiles are resolved against the folder of the file.
+    pub fn read(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read configuration file '{}'", path.display()))?;
+        let mut config_file = Self::parse(&content)
+            .with_context(|| format!("Failed to load configuration file '{}'", path.display()))?;
+
+        let config_folder = path.parent().unwrap_or(Path::new(""));
+        for profile in config_file.profiles.values_mut() {
+            if let Some(output_folder) = &profile.output_folder {
+                profile.output_folder = Some(config_folder.join(output_folder));
+            }
+        }
+        Ok(config_file)
+    }
+
+    /// Returns the profile named `name`.
+    pub fn profile(&self, name: &str) -> Result<&Profile> {
+        self.profiles.get(name).with_context(|| {
+            let mut names: Vec<&String> = self.profiles.keys().collect();
+            names.sort();
+            format!("Unknown profile '{}', available profiles: {:?}", name, names)
+        })
+    }
+}
+
+impl Profile {
+    /// Returns the input type overrides, by input type.
+    pub fn input_type_overrides(&self) -> Result<HashMap<InputType, InputTypeOverrides>> {
+        self.input_types
+            .iter()
+            .map(|(name, overrides)| {
+                let input_type = InputType::from_str(name, true)
+                    .map_err(|_| anyhow::anyhow!("Unknown input type '{}' in profile", name))?;
+                Ok((input_type, overrides.clone()))
+            })
+            .collect()
+    }
+}
+
+/// Loads the profile `profile_name`, or [DEFAULT_PROFILE], from the configuration file.
+///
+/// The file is `config_path`, or [DEFAULT_CONFIG_FILE] if it exists in the working directory.
+/// Without a file or a default profile, all options come from the command line.
+pub fn load_profile(config_path: Option<&Path>, profile_name: Option<&str>) -> Result<Profile> {
+    let config_path = match config_path {
+        Some(path) => Some(path.to_path_buf()),
+        None => Some(PathBuf::from(DEFAULT_CONFIG_FILE)).filter(|path| path.is_file()),
+    };
+
+    match (config_path, profile_name) {
+        (Some(path), Some(name)) => ConfigFile::read(&path)?.profile(name).cloned(),
+        (Some(path), None) => {
+            Ok(ConfigFile::read(&path)?.profiles.remove(DEFAULT_PROFILE).unwrap_or_default())
+        }
+        (None, Some(name)) => Err(anyhow::anyhow!(
+            "Profile '{}' requires a configuration file, none found at '{}'",
+            name,
+            DEFAULT_CONFIG_FILE
+        )),
+        (None, None) => Ok(Profile::default()),
+    }
+}
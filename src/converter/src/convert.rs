@@ -1,16 +1,72 @@
-use crate::io::read_to_string;
+use crate::{
+    io::{read_to_string, InputType},
+    validate::ValidationConfig,
+};
 use anyhow::{Context, Result};
 use catplus_common::graph::{
-    graph_builder::GraphBuilder, insert_into::InsertIntoGraph, namespaces::cat_resource,
+    graph_builder::GraphBuilder,
+    insert_into::InsertIntoGraph,
+    iri_base::IriBase,
+    namespaces::{allores, cat},
+    provenance::{InputFile, Parameter, Provenance, ProvenanceGraph, Software, User},
+    skolem::skolem_base,
+    utils::sha256_hex,
 };
+use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
 use serde::{de::DeserializeOwned, Deserialize};
-use std::path::{Path, PathBuf};
+use sophia_api::ns::NsTerm;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
 
-// Derive Deserialize and ValueEnum
+// Derive Deserialize and ValueEnum, with the same names
 #[derive(Deserialize, Debug, clap::ValueEnum, Clone)]
+#[serde(rename_all = "kebab-case")]
 pub enum RdfFormat {
     Turtle,
     Jsonld,
+    #[value(name = "ntriples")]
+    #[serde(rename = "ntriples")]
+    NTriples,
+    #[value(name = "nquads")]
+    #[serde(rename = "nquads")]
+    NQuads,
+    #[value(name = "rdfxml")]
+    #[serde(rename = "rdfxml")]
+    RdfXml,
+    Trig,
+    JsonldCompact,
+    /// Canonical N-Quads (RDFC-1.0)
+    Canonical,
+}
+
+impl RdfFormat {
+    /// Whether the format holds named graphs, such as the named provenance graph.
+    pub fn has_named_graphs(&self) -> bool {
+        matches!(self, RdfFormat::NQuads | RdfFormat::Trig)
+    }
+}
+
+/// Root node types to frame compacted JSON-LD on.
+#[derive(Deserialize, Debug, clap::ValueEnum, Clone, Copy)]
+pub enum JsonLdFrame {
+    /// cat:Campaign
+    Campaign,
+    /// cat:Batch
+    Batch,
+    /// allores:AFR_0002524 (liquid chromatography document)
+    LiquidChromatography,
+}
+
+impl JsonLdFrame {
+    pub fn iri(&self) -> NsTerm<'static> {
+        match self {
+            JsonLdFrame::Campaign => cat::Campaign,
+            JsonLdFrame::Batch => cat::Batch,
+            JsonLdFrame::LiquidChromatography => allores::AFR_0002524,
+        }
+    }
 }
 
 /// Configuration struct for the converter
@@ -20,9 +76,101 @@ pub struct ConverterConfig {
     pub format: RdfFormat,
     pub prefix: Option<String>,
     pub materialize: bool,
+    /// Use random blank node identifiers instead of identifiers derived from the content.
+    pub random_bnodes: bool,
+    /// URL of the JSON-LD context to reference in compacted JSON-LD, instead of embedding it.
+    pub context_url: Option<String>,
+    /// Root node type to frame compacted JSON-LD on.
+    pub frame: Option<JsonLdFrame>,
+    /// Content URI of the input, instead of building it from the input path.
+    /// Required when reading from stdin.
+    pub content_uri: Option<String>,
+    /// Graph to add PROV-O provenance of the conversion to, if any.
+    pub provenance: Option<ProvenanceGraph>,
+    /// Base IRIs of the minted resources and of the ontology.
+    pub iri_base: IriBase,
+    /// Options of specific input types, over the options above.
+    pub input_type_overrides: HashMap<InputType, InputTypeOverrides>,
+    /// Validation of the graphs against SHACL shapes before they are written, if any.
+    pub validation: Option<ValidationConfig>,
+}
+
+/// Options that apply to the files of a single input type, see [ConverterConfig::for_input_type].
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct InputTypeOverrides {
+    pub format: Option<RdfFormat>,
+    pub prefix: Option<String>,
+    pub materialize: Option<bool>,
+}
+
+impl ConverterConfig {
+    /// Returns the configuration with the overrides of `input_type` applied.
+    pub fn for_input_type(&self, input_type: InputType) -> ConverterConfig {
+        let mut config = self.clone();
+        if let Some(overrides) = self.input_type_overrides.get(&input_type) {
+            if let Some(format) = &overrides.format {
+                config.format = format.clone();
+            }
+            if let Some(prefix) = &overrides.prefix {
+                config.prefix = Some(prefix.clone());
+            }
+            if let Some(materialize) = overrides.materialize {
+                config.materialize = materialize;
+            }
+        }
+        config
+    }
+
+    /// Checks that a named provenance graph can be written in the format of every input type.
+    pub fn check_provenance_format(&self) -> Result<()> {
+        if self.provenance != Some(ProvenanceGraph::Named) {
+            return Ok(());
+        }
+        if !self.format.has_named_graphs() {
+            anyhow::bail!("A named provenance graph requires the \"nquads\" or \"trig\" format.");
+        }
+        for input_type in self.input_type_overrides.keys() {
+            let format = self.for_input_type(*input_type).format;
+            if !format.has_named_graphs() {
+                anyhow::bail!(
+                    "A named provenance graph requires the \"nquads\" or \"trig\" format, \
+                     but {:?} inputs are written as {:?}.",
+                    input_type,
+                    format
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether the input is read from stdin, with the path `-`.
+    pub fn is_stdin(&self) -> bool {
+        self.input_path == Path::new("-")
+    }
 }
 
+/// Characters of a path that are percent-encoded in the content file URI,
+/// so that it is a valid IRI.
+const PATH_ENCODE_SET: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'[')
+    .add(b'\\')
+    .add(b']')
+    .add(b'^')
+    .add(b'`')
+    .add(b'{')
+    .add(b'|')
+    .add(b'}');
+
 /// Builds the content file URI using an absolute path, or prefix and relative path.
+/// The path is percent-encoded.
 fn build_file_uri(prefix: Option<String>, path: &Path) -> Result<String> {
     if let Some(ref p) = prefix {
         if p.is_empty() {
@@ -30,9 +178,10 @@ fn build_file_uri(prefix: Option<String>, path: &Path) -> Result<String> {
         }
     }
 
+    let encoded_path = utf8_percent_encode(&path.to_string_lossy(), PATH_ENCODE_SET).to_string();
     match (prefix, path.is_absolute()) {
         // Prefix, Relative path
-        (Some(p), false) => return Ok(format!("{}{}", p, path.to_string_lossy())),
+        (Some(p), false) => return Ok(format!("{}{}", p, encoded_path)),
         // No prefix, relative path
         (_, false) => {
             return Err(anyhow::anyhow!("Cannot build URI for relative path without a prefix."))
@@ -40,42 +189,154 @@ fn build_file_uri(prefix: Option<String>, path: &Path) -> Result<String> {
         // Absolute path -> ignore prefix
         (p, true) => {
             if p.is_some() {
-                println!("Prefix is ignored with absolute paths")
+                eprintln!("Prefix is ignored with absolute paths")
             }
-            return Ok(format!("file://{}", path.to_string_lossy()));
+            return Ok(format!("file://{}", encoded_path));
         }
     }
 }
 
+/// Builds the URI of an output file as [build_file_uri], relative to the prefix of `config`
+/// if there is one, or from its absolute path otherwise.
+pub(crate) fn output_file_uri(config: &ConverterConfig, path: &Path) -> Result<String> {
+    let path = match config.prefix {
+        Some(_) => path.to_path_buf(),
+        None => std::path::absolute(path)
+            .with_context(|| format!("Invalid output path '{}'.", path.display()))?,
+    };
+    build_file_uri(config.prefix.clone(), &path).context("Failed to build file URI")
+}
+
+/// Returns the content URI given in `config`, or builds it from the input path.
+fn content_uri(config: &ConverterConfig) -> Result<String> {
+    match &config.content_uri {
+        Some(uri) => Ok(uri.clone()),
+        None if config.is_stdin() => {
+            Err(anyhow::anyhow!("A content URI must be given when reading from stdin."))
+        }
+        None => build_file_uri(config.prefix.clone(), Path::new(&config.input_path))
+            .context("Failed to build file URI"),
+    }
+}
+
 /// Parses JSON and serializes the RDF graph to the specified format.
 ///
 /// This function can handle any struct that implements `serde::DeserializeOwned` and your `InsertIntoGraph` trait.
 ///
 /// # Arguments
-/// - `input_content`: The JSON input as a string.
-/// - `format`: The desired serialization format.
+/// - `config`: The converter configuration, with the input path and the desired serialization format.
 ///
 /// # Returns
 /// A `Result` containing the serialized graph as a string or an error.
 pub fn json_to_rdf<T>(config: &ConverterConfig) -> Result<String>
+where
+    T: DeserializeOwned + InsertIntoGraph, // Trait bounds
+{
+    let graph_builder = json_to_graph::<T>(config)?;
+    serialize_graph(&graph_builder, config)
+}
+
+/// Parses JSON into an RDF graph, without serializing it.
+///
+/// The content URI is linked, the IRIs are rebased and blank nodes are materialized
+/// according to `config`.
+pub fn json_to_graph<T>(config: &ConverterConfig) -> Result<GraphBuilder>
 where
     T: DeserializeOwned + InsertIntoGraph, // Trait bounds
 {
     let input_content = read_to_string(Path::new(&config.input_path))?;
-    let instances: T = parse_json(&input_content).context("Failed to parse JSON input")?;
+    json_str_to_graph::<T>(&input_content, config, None)
+}
+
+/// Parses a JSON string into an RDF graph, see [json_to_graph].
+///
+/// The input type, if known, is recorded in the provenance.
+pub fn json_str_to_graph<T>(
+    input_content: &str,
+    config: &ConverterConfig,
+    input_type: Option<InputType>,
+) -> Result<GraphBuilder>
+where
+    T: DeserializeOwned + InsertIntoGraph, // Trait bounds
+{
+    let instances: T = parse_json(input_content).context("Failed to parse JSON input")?;
     let mut graph_builder = GraphBuilder::new();
+    graph_builder.random_bnodes = config.random_bnodes;
     graph_builder.insert(&instances)?;
 
-    let uri = build_file_uri(config.prefix.clone(), Path::new(&config.input_path))
-        .context("Failed to build file URI")?;
+    let uri = content_uri(config)?;
     graph_builder.link_content(&uri).context("Failed to add content URL to the graph")?;
 
+    if let Some(provenance_graph) = config.provenance {
+        let provenance = build_provenance(input_content, config, input_type, &uri);
+        graph_builder
+            .add_provenance(&provenance, provenance_graph)
+            .context("Failed to add provenance to the graph")?;
+    }
+
+    if !config.iri_base.is_default() {
+        graph_builder.rebase(config.iri_base.clone())?;
+    }
+
     if config.materialize {
         graph_builder
-            .materialize_blank_nodes(Some(&cat_resource::ns.clone().to_string()))
+            .skolemize(&skolem_base(&config.iri_base.resource))
             .context("Failed to materialize blank nodes")?;
     }
 
+    Ok(graph_builder)
+}
+
+/// Describes the conversion of `input_content` with the options of `config`.
+fn build_provenance(
+    input_content: &str,
+    config: &ConverterConfig,
+    input_type: Option<InputType>,
+    content_url: &str,
+) -> Provenance {
+    let mut parameters = vec![];
+    if let Some(input_type) = input_type {
+        parameters.push(Parameter::new("input-type", format!("{:?}", input_type)));
+    }
+    parameters.push(Parameter::new("format", format!("{:?}", config.format)));
+    if let Some(prefix) = &config.prefix {
+        parameters.push(Parameter::new("prefix", prefix));
+    }
+    parameters.push(Parameter::new("materialize", config.materialize));
+    parameters.push(Parameter::new("random-bnodes", config.random_bnodes));
+    if !config.iri_base.is_default() {
+        parameters.push(Parameter::new("resource-base", &config.iri_base.resource));
+        parameters.push(Parameter::new("ontology-iri", &config.iri_base.ontology));
+    }
+
+    Provenance {
+        software: Software {
+            name: env!("CARGO_PKG_NAME").to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+        },
+        user: std::env::var("USER")
+            .or_else(|_| std::env::var("USERNAME"))
+            .ok()
+            .map(|name| User { name }),
+        input: InputFile {
+            content_url: Some(content_url.to_string()),
+            sha256: sha256_hex(input_content.as_bytes()),
+        },
+        ended_at: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+        parameters,
+    }
+}
+
+/// Serializes the graph to the format specified in `config`.
+///
+/// Fails if the graph has a named provenance graph that the format cannot hold.
+pub fn serialize_graph(graph_builder: &GraphBuilder, config: &ConverterConfig) -> Result<String> {
+    if graph_builder.provenance_graph.is_some() && !config.format.has_named_graphs() {
+        anyhow::bail!(
+            "The named provenance graph cannot be written in the {:?} format.",
+            config.format
+        );
+    }
     let serialized_graph = match &config.format {
         RdfFormat::Jsonld => {
             graph_builder.serialize_to_jsonld().context("Failed to serialize to JSON-LD")?
@@ -83,6 +344,28 @@ where
         RdfFormat::Turtle => {
             graph_builder.serialize_to_turtle().context("Failed to serialize to Turtle")?
         }
+        RdfFormat::NTriples => {
+            graph_builder.serialize_to_ntriples().context("Failed to serialize to N-Triples")?
+        }
+        RdfFormat::NQuads => {
+            graph_builder.serialize_to_nquads().context("Failed to serialize to N-Quads")?
+        }
+        RdfFormat::RdfXml => {
+            graph_builder.serialize_to_rdfxml().context("Failed to serialize to RDF/XML")?
+        }
+        RdfFormat::Trig => {
+            graph_builder.serialize_to_trig().context("Failed to serialize to TriG")?
+        }
+        RdfFormat::Canonical => {
+            graph_builder.canonicalize().context("Failed to serialize to canonical N-Quads")?
+        }
+        RdfFormat::JsonldCompact => {
+            let frame_type =
+                config.frame.map(|frame| config.iri_base.rebase_iri(&frame.iri().to_string()));
+            graph_builder
+                .serialize_to_compact_jsonld(config.context_url.as_deref(), frame_type.as_deref())
+                .context("Failed to serialize to compacted JSON-LD")?
+        }
     };
 
     Ok(serialized_graph)
@@ -0,0 +1,183 @@
+use crate::{
+    batch::{process_file, run_in_order, FileResult, Outcome},
+    convert::ConverterConfig,
+    io::InputType,
+};
+use anyhow::{Context, Result};
+use catplus_common::graph::utils::sha256_hex;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+/// Name of the manifest written in the output folder.
+pub const MANIFEST_FILE: &str = ".catplus-manifest.json";
+
+/// Conversion of an input file recorded in the [Manifest].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ManifestEntry {
+    /// Hex-encoded SHA-256 digest of the input content.
+    pub input_sha256: String,
+    /// Options the file was converted with, see [options_fingerprint].
+    pub options: String,
+    pub output_path: PathBuf,
+}
+
+/// Sidecar manifest of the converted files, so that unchanged files are not converted again.
+///
+/// Inputs are compared by content rather than modification time, which copies and
+/// archive extraction do not preserve.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct Manifest {
+    pub files: BTreeMap<PathBuf, ManifestEntry>,
+}
+
+/// Describes the options of `config` and the explicit `input_type` that change the output
+/// of a conversion.
+pub fn options_fingerprint(config: &ConverterConfig, input_type: Option<InputType>) -> String {
+    let mut overrides: Vec<String> = config
+        .input_type_overrides
+        .iter()
+        .map(|(input_type, overrides)| format!("{:?}={:?}", input_type, overrides))
+        .collect();
+    overrides.sort();
+    format!(
+        "input-type={:?} format={:?} prefix={:?} materialize={} random-bnodes={} context-url={:?} frame={:?} \
+         content-uri={:?} provenance={:?} resource-base={} ontology-iri={} overrides={:?} \
+         validation={:?}",
+        input_type,
+        config.format,
+        config.prefix,
+        config.materialize,
+        config.random_bnodes,
+        config.context_url,
+        config.frame,
+        config.content_uri,
+        config.provenance,
+        config.iri_base.resource,
+        config.iri_base.ontology,
+        overrides,
+        config.validation.as_ref().map(|validation| (
+            &validation.shapes_sha256,
+            &validation.endpoint,
+            validation.on_invalid
+        ))
+    )
+}
+
+impl Manifest {
+    /// Reads the manifest at `path`, or returns an empty manifest if it does not exist.
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read manifest '{}'", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Invalid manifest '{}'", path.display()))
+    }
+
+    /// Writes the manifest at `path`, replacing it at once so that it is never half-written.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let content = serde_json::to_string_pretty(self).context("Failed to serialize manifest")?;
+        let temporary_path = path.with_extension("tmp");
+        fs::write(&temporary_path, content)
+            .with_context(|| format!("Failed to write manifest '{}'", path.display()))?;
+        fs::rename(&temporary_path, path)
+            .with_context(|| format!("Failed to write manifest '{}'", path.display()))
+    }
+
+    /// Returns the output of `input_path` if it was converted from the same content with
+    /// the same options and input type, and still exists.
+    pub fn up_to_date_output(
+        &self,
+        input_path: &Path,
+        config: &ConverterConfig,
+        input_type: Option<InputType>,
+    ) -> Result<Option<PathBuf>> {
+        let Some(entry) = self.files.get(input_path) else {
+            return Ok(None);
+        };
+        if entry.options != options_fingerprint(config, input_type) || !entry.output_path.exists() {
+            return Ok(None);
+        }
+        let content = fs::read(input_path)
+            .with_context(|| format!("Failed to read input file '{}'.", input_path.display()))?;
+        Ok((entry.input_sha256 == sha256_hex(&content)).then(|| entry.output_path.clone()))
+    }
+
+    /// Processes `inputs` as [crate::batch::process_files], except the files that are
+    /// up to date, which are reported with [Outcome::UpToDate] in the order of `inputs`.
+    pub fn process_files<F>(
+        &self,
+        inputs: &[(PathBuf, PathBuf)],
+        config: &ConverterConfig,
+        input_type: Option<InputType>,
+        jobs: usize,
+        keep_going: bool,
+        mut on_result: F,
+    ) -> Vec<FileResult>
+    where
+        F: FnMut(&FileResult),
+    {
+        run_in_order(
+            inputs.len(),
+            jobs,
+            keep_going,
+            |index| {
+                let (input_path, output_folder) = &inputs[index];
+                let start = Instant::now();
+                // Files that cannot be checked are converted, and fail there if unreadable.
+                if let Ok(Some(output_path)) =
+                    self.up_to_date_output(input_path, config, input_type)
+                {
+                    let outcome = Outcome::UpToDate { output_path };
+                    let result = FileResult {
+                        input_path: input_path.clone(),
+                        input_type: None,
+                        rule: None,
+                        input_sha256: None,
+                        outcome,
+                        elapsed: start.elapsed(),
+                    };
+                    return (result, ());
+                }
+                let mut file_config = config.clone();
+                file_config.input_path = input_path.clone();
+                (process_file(&file_config, output_folder, input_type), ())
+            },
+            |result, _| on_result(result),
+        )
+    }
+
+    /// Records the files converted in `results`, with the digest of the content they were
+    /// converted from, so that files changed during the run are converted again.
+    ///
+    /// Files whose graph does not conform to the shapes are not recorded, so that they
+    /// are converted and reported as invalid again on the next run.
+    pub fn record(
+        &mut self,
+        results: &[FileResult],
+        config: &ConverterConfig,
+        input_type: Option<InputType>,
+    ) {
+        let options = options_fingerprint(config, input_type);
+        for result in results {
+            if let (Outcome::Converted { output_path, .. }, Some(input_sha256)) =
+                (&result.outcome, &result.input_sha256)
+            {
+                self.files.insert(
+                    result.input_path.clone(),
+                    ManifestEntry {
+                        input_sha256: input_sha256.clone(),
+                        options: options.clone(),
+                        output_path: output_path.clone(),
+                    },
+                );
+            }
+        }
+    }
+}
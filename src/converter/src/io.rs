@@ -1,14 +1,14 @@
-use crate::convert::RdfFormat;
+use crate::{convert::RdfFormat, incremental::MANIFEST_FILE, watch::DEFAULT_STATE_FILE};
 
 use anyhow::{Context, Result};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::{
-    fs::File,
+    fs::{self, File},
     io::{Read, Write},
     path::{Path, PathBuf},
 };
 
-#[derive(Deserialize, Debug, clap::ValueEnum, Clone)]
+#[derive(Serialize, Deserialize, Debug, clap::ValueEnum, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum InputType {
     Synth,
     HCI,
@@ -24,12 +24,16 @@ impl RdfFormatExt for RdfFormat {
     fn extension(&self) -> &'static str {
         match self {
             RdfFormat::Turtle => "ttl",
-            RdfFormat::Jsonld => "jsonld",
+            RdfFormat::Jsonld | RdfFormat::JsonldCompact => "jsonld",
+            RdfFormat::NTriples => "nt",
+            RdfFormat::NQuads | RdfFormat::Canonical => "nq",
+            RdfFormat::RdfXml => "rdf",
+            RdfFormat::Trig => "trig",
         }
     }
 }
 
-pub(crate) fn read_to_string(path: &Path) -> Result<String> {
+pub fn read_to_string(path: &Path) -> Result<String> {
     let mut content = String::new();
     File::open(path)
         .with_context(|| format!("Failed to open file '{}'.", path.display()))?
@@ -38,33 +42,89 @@ pub(crate) fn read_to_string(path: &Path) -> Result<String> {
     Ok(content)
 }
 
-fn detect_input_type(filename: &str) -> Option<InputType> {
-    let lowercase = filename.to_lowercase();
-    if lowercase.contains("synth") {
-        Some(InputType::Synth)
-    } else if lowercase.contains("hci") {
-        Some(InputType::HCI)
-    } else if lowercase.contains("agilent") {
-        Some(InputType::Agilent)
-    } else if lowercase.contains("bravo") {
-        Some(InputType::Bravo)
+/// The rule that determined the input type of a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DetectionRule {
+    /// The input type was given explicitly by the user.
+    Explicit,
+    /// A characteristic top-level key was found in the JSON content.
+    Content(&'static str),
+    /// The filename contains the name of the input type.
+    Filename(&'static str),
+}
+
+impl std::fmt::Display for DetectionRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DetectionRule::Explicit => write!(f, "explicit input type"),
+            DetectionRule::Content(rule) => write!(f, "content: {}", rule),
+            DetectionRule::Filename(rule) => write!(f, "filename contains '{}'", rule),
+        }
+    }
+}
+
+/// Detects the input type from the top-level keys of the JSON content.
+/// Returns `None` if the content is not a JSON object or no rule matches.
+pub fn detect_input_type_from_content(content: &str) -> Option<(InputType, DetectionRule)> {
+    let value: serde_json::Value = serde_json::from_str(content).ok()?;
+    let object = value.as_object()?;
+
+    if object.contains_key("hasCampaign") {
+        Some((InputType::HCI, DetectionRule::Content("'hasCampaign' key")))
+    } else if object.contains_key("liquid chromatography aggregate document") {
+        Some((
+            InputType::Agilent,
+            DetectionRule::Content("'liquid chromatography aggregate document' key"),
+        ))
+    } else if object.contains_key("Actions") && object.contains_key("batchID") {
+        Some((InputType::Synth, DetectionRule::Content("'Actions' and 'batchID' keys")))
+    } else if object.contains_key("Actions") {
+        Some((InputType::Bravo, DetectionRule::Content("'Actions' key without 'batchID'")))
     } else {
         None
     }
 }
 
+/// Detects the input type from substrings of the filename.
+pub fn detect_input_type_from_filename(filename: &str) -> Option<(InputType, DetectionRule)> {
+    let lowercase = filename.to_lowercase();
+    [
+        ("synth", InputType::Synth),
+        ("hci", InputType::HCI),
+        ("agilent", InputType::Agilent),
+        ("bravo", InputType::Bravo),
+    ]
+    .into_iter()
+    .find(|(pattern, _)| lowercase.contains(pattern))
+    .map(|(pattern, input_type)| (input_type, DetectionRule::Filename(pattern)))
+}
+
 pub enum InputAction {
-    Skip(String), // reason
-    Process(InputType),
+    Skip(String),                              // reason
+    Process(InputType, DetectionRule, String), // content
 }
+
 /// Decide what action to take on an input file.
-pub fn determine_input_action(input_path: &Path) -> Result<InputAction> {
+///
+/// An explicit `input_type` takes precedence, then the JSON content is inspected,
+/// and the filename is used as a fallback. JSON files are read once, and their content
+/// is passed on for conversion. Unreadable files are skipped unless their type is known,
+/// in which case the read error is returned.
+pub fn determine_input_action(
+    input_path: &Path,
+    input_type: Option<InputType>,
+) -> Result<InputAction> {
     let filename = input_path
         .file_name()
         .ok_or_else(|| anyhow::anyhow!("Input path has no filename"))?
         .to_str()
         .ok_or_else(|| anyhow::anyhow!("Filename is not valid UTF-8"))?;
-    if filename.ends_with(".ttl") || filename.ends_with(".jsonld") {
+    if [MANIFEST_FILE, DEFAULT_STATE_FILE].contains(&filename) {
+        return Ok(InputAction::Skip("State file of the converter.".to_string()));
+    } else if [".ttl", ".jsonld", ".nt", ".nq", ".rdf", ".trig"]
+        .iter()
+        .any(|ext| filename.ends_with(ext))
+    {
         return Ok(InputAction::Skip("Already in RDF.".to_string()));
     } else if !filename.ends_with(".json") {
         return Ok(InputAction::Skip(
@@ -72,11 +132,22 @@ pub fn determine_input_action(input_path: &Path) -> Result<InputAction> {
         ));
     }
 
-    let action = match detect_input_type(filename) {
-        Some(input_type) => InputAction::Process(input_type),
+    let content = read_to_string(input_path);
+    if let Some(input_type) = input_type {
+        return Ok(InputAction::Process(input_type, DetectionRule::Explicit, content?));
+    }
+
+    let detected = content
+        .as_deref()
+        .ok()
+        .and_then(detect_input_type_from_content)
+        .or_else(|| detect_input_type_from_filename(filename));
+
+    let action = match detected {
+        Some((input_type, rule)) => InputAction::Process(input_type, rule, content?),
         None => InputAction::Skip("No matching type.".to_string()),
     };
-    return Ok(action);
+    Ok(action)
 }
 
 pub fn save_output(
@@ -84,7 +155,7 @@ pub fn save_output(
     output_folder: &Path,
     serialized_graph: &str,
     format: &RdfFormat,
-) -> Result<()> {
+) -> Result<PathBuf> {
     let stem = input_path.file_stem().and_then(|s| s.to_str()).context("Invalid file stem")?;
     let output_filename = format!("{}.{}", stem, format.extension());
     let output_path = output_folder.join(output_filename);
@@ -95,9 +166,44 @@ pub fn save_output(
         .write_all(serialized_graph.as_bytes())
         .with_context(|| format!("Failed to write to output file '{}'.", output_path.display()))?;
 
-    println!("Processed '{}' -> '{}'", input_path.display(), output_path.display());
+    Ok(output_path)
+}
+
+/// Lists the files in a folder, sorted by path.
+/// If `recursive` is set, files in all subfolders are included as well.
+/// Symbolic links to folders are not followed.
+pub fn collect_input_files(folder: &Path, recursive: bool) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut folders = vec![folder.to_path_buf()];
 
-    Ok(())
+    while let Some(current) = folders.pop() {
+        let entries = fs::read_dir(&current)
+            .with_context(|| format!("Failed to read folder '{}'.", current.display()))?;
+        for entry in entries {
+            let entry = entry?;
+            let file_type = entry.file_type()?;
+            if file_type.is_dir() {
+                if recursive {
+                    folders.push(entry.path());
+                }
+            } else if entry.path().is_file() {
+                files.push(entry.path());
+            }
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+/// Mirrors the location of `input_path` relative to `input_root` under `output_root`.
+/// Returns the output folder for the file.
+pub fn mirror_output_folder(input_root: &Path, input_path: &Path, output_root: &Path) -> PathBuf {
+    let parent = input_path.parent().unwrap_or(Path::new(""));
+    match parent.strip_prefix(input_root) {
+        Ok(relative) => output_root.join(relative),
+        Err(_) => output_root.to_path_buf(),
+    }
 }
 
 /// Defines a fallback output folder based on input file in case  it is missing.
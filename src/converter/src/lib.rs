@@ -1,2 +1,8 @@
+pub mod batch;
+pub mod config_file;
 pub mod convert;
+pub mod incremental;
 pub mod io;
+pub mod report;
+pub mod validate;
+pub mod watch;
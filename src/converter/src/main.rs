@@ -1,86 +1,347 @@
-use catplus_common::models::{
-    agilent::LiquidChromatographyAggregateDocumentWrapper, bravo::BravoActionWrapper,
-    hci::CampaignWrapper, synth::SynthBatch,
+use anyhow::{Context, Result};
+use catplus_common::graph::{
+    graph_builder::GraphBuilder, iri_base::IriBase, provenance::ProvenanceGraph,
 };
+use clap::Parser;
 use converter::{
-    convert::{json_to_rdf, ConverterConfig, RdfFormat},
-    io::{define_output_folder, determine_input_action, save_output, InputAction, InputType},
+    batch::{
+        build_graph_from_str, merge_files, print_folder_counts, print_summary, process_files,
+        FileResult, Outcome,
+    },
+    config_file::{load_profile, Profile},
+    convert::{serialize_graph, ConverterConfig, JsonLdFrame, RdfFormat},
+    incremental::{Manifest, MANIFEST_FILE},
+    io::{
+        collect_input_files, define_output_folder, detect_input_type_from_content,
+        mirror_output_folder, InputType,
+    },
+    report::ConversionReport,
+    validate::{
+        nonconformance, save_validation_report, OnInvalid, ValidationConfig, QUARANTINE_FOLDER,
+    },
+    watch::{Watcher, DEFAULT_STATE_FILE},
 };
-use std::convert::Into;
-
-use anyhow::{Context, Result};
-use clap::Parser;
+use sophia_api::graph::Graph;
 use std::{
     fs::{self},
+    io::{stdin, stdout, Read, Write},
     path::{Path, PathBuf},
+    process::ExitCode,
+    time::Duration,
 };
 
 #[derive(Parser, Debug)]
 struct Args {
     /// Path to the input file or folder containing files.
+    /// Use "-" to read JSON from stdin and write RDF to stdout.
     input_path: PathBuf,
-    /// batch/2025/10/01/24/ ....
+
+    /// Output RDF format: "turtle", "jsonld", "jsonld-compact", "ntriples", "nquads", "rdfxml" or "trig".
+    /// Required unless the profile sets it.
+    #[arg(value_enum)]
+    format: Option<RdfFormat>,
 
     /// Path to the output folder. Defaults to input folder if not specified.
     #[arg(long)]
     output_folder: Option<PathBuf>,
 
-    /// Output RDF format: "Turtle" or "Jsonld".
-    #[arg(value_enum)]
-    format: RdfFormat,
+    /// Path to the TOML configuration file with the profiles.
+    /// Defaults to "catplus.toml" in the working directory, if it exists.
+    #[arg(long)]
+    config: Option<PathBuf>,
+
+    /// Profile of the configuration file to use, "default" if not specified.
+    /// The command line flags take precedence over the profile.
+    #[arg(long)]
+    profile: Option<String>,
 
     /// Prefix to use for relative content URIs.
     #[arg(long)]
     prefix: Option<String>,
 
-    /// Materialize blank nodes
-    #[arg(long, default_value_t = false)]
+    /// Content URI of the input, instead of building it from the input path.
+    /// Required when reading from stdin.
+    #[arg(long)]
+    content_uri: Option<String>,
+
+    /// Materialize blank nodes as skolem IRIs under "/.well-known/genid/".
+    #[arg(long, overrides_with = "no_materialize")]
     materialize: bool,
+
+    /// Keep blank nodes, even if the profile materializes them.
+    #[arg(long, overrides_with = "materialize")]
+    no_materialize: bool,
+
+    /// Use random blank node identifiers instead of identifiers derived from the content.
+    #[arg(long, default_value_t = false)]
+    random_bnodes: bool,
+
+    /// Add PROV-O provenance of the conversion, in the "default" graph or in a
+    /// separate "named" graph. The named graph requires "nquads" or "trig" output.
+    #[arg(long, value_enum)]
+    provenance: Option<ProvenanceGraph>,
+
+    /// Base IRI of the minted resources, ending with "/" or "#".
+    /// Defaults to "http://example.org/cat/resource/".
+    #[arg(long)]
+    resource_base: Option<String>,
+
+    /// IRI of the cat+ ontology, ending with "/" or "#".
+    /// Defaults to "http://example.org/catplus/ontology/".
+    #[arg(long)]
+    ontology_iri: Option<String>,
+
+    /// URL of the JSON-LD context to reference with "jsonld-compact".
+    /// The context is embedded inline if not specified.
+    #[arg(long)]
+    context_url: Option<String>,
+
+    /// Root node type to frame the "jsonld-compact" output on.
+    #[arg(long, value_enum)]
+    frame: Option<JsonLdFrame>,
+
+    /// Convert files in all subfolders, e.g. batch/2025/10/01/24/, and mirror
+    /// the folder layout under the output folder.
+    #[arg(long, default_value_t = false)]
+    recursive: bool,
+
+    /// Continue with the remaining files when a file fails to convert.
+    /// The exit code is non-zero if any file failed.
+    #[arg(long, default_value_t = false)]
+    keep_going: bool,
+
+    /// Number of files to convert concurrently.
+    #[arg(short, long, default_value_t = 1)]
+    jobs: usize,
+
+    /// Write a JSON report of the outcome of every file to this path.
+    #[arg(long)]
+    report: Option<PathBuf>,
+
+    /// Input type of the files. Detected from the content or filename if not specified.
+    #[arg(long, value_enum)]
+    input_type: Option<InputType>,
+
+    /// Only convert the files whose content or options changed since their last conversion,
+    /// as recorded in ".catplus-manifest.json" in the output folder.
+    #[arg(long, default_value_t = false)]
+    incremental: bool,
+
+    /// Convert all files with --incremental, even those that are up to date.
+    #[arg(long, default_value_t = false, requires = "incremental")]
+    force: bool,
+
+    /// Keep watching the input folder and convert the JSON files that are added or modified.
+    #[arg(long, default_value_t = false)]
+    watch: bool,
+
+    /// Seconds a watched file must be left unmodified before it is converted.
+    #[arg(long, default_value_t = 5)]
+    settle_delay: u64,
+
+    /// Seconds between two scans of the watched folder.
+    #[arg(long, default_value_t = 2)]
+    poll_interval: u64,
+
+    /// Path of the file remembering the converted files in watch mode.
+    /// Defaults to ".catplus-watch-state.json" in the output folder.
+    #[arg(long)]
+    state_file: Option<PathBuf>,
+
+    /// Merge the graphs of all input files into a single graph written to this path,
+    /// and report the references that do not resolve between the files.
+    #[arg(long)]
+    merge: Option<PathBuf>,
+
+    /// Validate the graphs against SHACL shapes before they are written.
+    #[arg(long, default_value_t = false)]
+    validate: bool,
+
+    /// Path to the SHACL shapes to validate with, evaluated by the native engine.
+    #[arg(long, requires = "validate")]
+    shapes: Option<PathBuf>,
+
+    /// Endpoint of a SHACL API server to validate with, instead of the native engine.
+    /// The server uses its default shapes if --shapes is not given.
+    #[arg(long, requires = "validate")]
+    shacl_endpoint: Option<String>,
+
+    /// What to do with graphs that do not conform: "fail" the file, "warn" and write the
+    /// output, or "quarantine" the output with its validation report in "quarantine/" under
+    /// the output folder.
+    #[arg(long, value_enum, default_value = "fail", requires = "validate")]
+    on_invalid: OnInvalid,
 }
 
-impl Into<ConverterConfig> for Args {
-    fn into(self) -> ConverterConfig {
-        ConverterConfig {
-            input_path: self.input_path,
-            format: self.format,
-            prefix: self.prefix,
-            materialize: self.materialize,
+impl Args {
+    /// Whether blank nodes are materialized, if given with --materialize or --no-materialize.
+    fn materialize(&self) -> Option<bool> {
+        match (self.materialize, self.no_materialize) {
+            (true, _) => Some(true),
+            (_, true) => Some(false),
+            _ => None,
+        }
+    }
+
+    /// Builds the converter configuration, filling the flags that are not given from `profile`.
+    fn into_config(self, profile: &Profile) -> Result<ConverterConfig> {
+        let format = self.format.clone().or_else(|| profile.format.clone()).context(
+            "An output format must be given on the command line or in the configuration profile.",
+        )?;
+        let iri_base = IriBase::new(
+            self.resource_base.as_deref().or(profile.resource_base.as_deref()),
+            self.ontology_iri.as_deref().or(profile.ontology_iri.as_deref()),
+        )?;
+        let materialize = self.materialize();
+
+        // Flags given on the command line also take precedence over the input type overrides.
+        let mut input_type_overrides = profile.input_type_overrides()?;
+        for overrides in input_type_overrides.values_mut() {
+            if self.format.is_some() {
+                overrides.format = None;
+            }
+            if self.prefix.is_some() {
+                overrides.prefix = None;
+            }
+            if materialize.is_some() {
+                overrides.materialize = None;
+            }
         }
+
+        let validation = self
+            .validate
+            .then(|| {
+                ValidationConfig::new(self.shapes.as_deref(), self.shacl_endpoint, self.on_invalid)
+            })
+            .transpose()?;
+
+        Ok(ConverterConfig {
+            input_path: self.input_path,
+            format,
+            prefix: self.prefix.or_else(|| profile.prefix.clone()),
+            materialize: materialize.or(profile.materialize).unwrap_or(false),
+            random_bnodes: self.random_bnodes,
+            context_url: self.context_url,
+            frame: self.frame,
+            content_uri: self.content_uri,
+            provenance: self.provenance,
+            iri_base,
+            input_type_overrides,
+            validation,
+        })
     }
 }
 
-fn process_file(config: ConverterConfig, output_folder: &Path) -> Result<()> {
-    let input_path = config.input_path.clone();
-    let format = config.format.clone();
-    let input_type = match determine_input_action(input_path.as_path())? {
-        InputAction::Skip(reason) => {
-            println!("Skipping file '{}': {}", input_path.display(), reason);
-            return Ok(());
+/// Converts JSON from stdin and writes the RDF graph to stdout.
+fn convert_stdin(config: &ConverterConfig, input_type: Option<InputType>) -> Result<()> {
+    if config.content_uri.is_none() {
+        anyhow::bail!("--content-uri is required when reading from stdin.");
+    }
+    let mut input_content = String::new();
+    stdin().read_to_string(&mut input_content).context("Failed to read from stdin")?;
+
+    let input_type = match input_type {
+        Some(input_type) => input_type,
+        None => {
+            let (input_type, rule) = detect_input_type_from_content(&input_content)
+                .context("Cannot detect the input type from stdin, use --input-type.")?;
+            eprintln!("Detected {:?} input from stdin ({})", input_type, rule);
+            input_type
         }
-        InputAction::Process(input_type) => input_type,
     };
 
-    let serialized_graph = match input_type {
-        InputType::HCI => json_to_rdf::<CampaignWrapper>(&config),
-        InputType::Synth => json_to_rdf::<SynthBatch>(&config),
-        InputType::Agilent => json_to_rdf::<LiquidChromatographyAggregateDocumentWrapper>(&config),
-        InputType::Bravo => json_to_rdf::<BravoActionWrapper>(&config),
+    let graph_builder = build_graph_from_str(&input_content, config, input_type)
+        .context("Failed to convert stdin to RDF")?;
+    if let Some(validation) = &config.validation {
+        let report = validation.validate(&graph_builder.graph)?;
+        if !report.conforms {
+            match validation.on_invalid {
+                OnInvalid::Warn => eprintln!("Warning: invalid stdin: {}", nonconformance(&report)),
+                _ => anyhow::bail!("Invalid stdin: {}", nonconformance(&report)),
+            }
+        }
     }
-    .with_context(|| {
-        format!("Failed to convert '{}' to RDF format '{:?}'", input_path.display(), format)
-    })?;
+    let serialized_graph = serialize_graph(&graph_builder, &config.for_input_type(input_type))?;
 
-    save_output(&config.input_path, &output_folder, &serialized_graph, &config.format)?;
+    let mut output = stdout().lock();
+    output.write_all(serialized_graph.as_bytes()).context("Failed to write to stdout")?;
+    output.flush().context("Failed to write to stdout")?;
+    Ok(())
+}
 
+/// Serializes the merged graph of all input files to `merge_path`.
+///
+/// With validation, a non-conforming merged graph is handled with the [OnInvalid] policy,
+/// and quarantined in the "quarantine" folder next to `merge_path`.
+fn write_merged_graph(
+    graph_builder: &GraphBuilder,
+    config: &ConverterConfig,
+    merge_path: &Path,
+) -> Result<()> {
+    // Validation report of a merged graph that does not conform, to quarantine it with.
+    let mut quarantine_report = None;
+    let mut merge_path = merge_path.to_path_buf();
+    if let Some(validation) = &config.validation {
+        let report = validation.validate(&graph_builder.graph)?;
+        if !report.conforms {
+            let reason = nonconformance(&report);
+            match validation.on_invalid {
+                OnInvalid::Fail => anyhow::bail!("Invalid merged graph: {}", reason),
+                OnInvalid::Warn => eprintln!("Warning: invalid merged graph: {}", reason),
+                OnInvalid::Quarantine => {
+                    eprintln!("Quarantining invalid merged graph: {}", reason);
+                    let file_name = merge_path.file_name().context("Invalid merge path")?;
+                    merge_path = merge_path.with_file_name(QUARANTINE_FOLDER).join(file_name);
+                    quarantine_report = Some(report);
+                }
+            }
+        }
+    }
+    let serialized_graph = serialize_graph(graph_builder, config)?;
+    if let Some(parent) = merge_path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create output folder '{}'.", parent.display()))?;
+    }
+    fs::write(&merge_path, serialized_graph)
+        .with_context(|| format!("Failed to write merged graph to '{}'.", merge_path.display()))?;
+    if let Some(report) = quarantine_report {
+        save_validation_report(&report, &graph_builder.graph, &merge_path)?;
+    }
+    println!(
+        "Merged {} triples into '{}'",
+        graph_builder.graph.triples().count(),
+        merge_path.display()
+    );
     Ok(())
 }
 
-fn main() -> Result<()> {
+fn main() -> Result<ExitCode> {
     let args = Args::parse();
+    let profile = load_profile(args.config.as_deref(), args.profile.as_deref())?;
 
     let input_path = args.input_path.clone();
-    let output_folder = args.output_folder.clone();
-    let config: ConverterConfig = args.into();
+    let output_folder = args.output_folder.clone().or_else(|| profile.output_folder.clone());
+    let input_type = args.input_type;
+    let recursive = args.recursive;
+    let keep_going = args.keep_going;
+    let report = args.report.clone();
+    let jobs = args.jobs;
+    let merge = args.merge.clone();
+    let watch = args.watch;
+    let incremental = args.incremental;
+    let force = args.force;
+    let settle_delay = Duration::from_secs(args.settle_delay);
+    let poll_interval = Duration::from_secs(args.poll_interval);
+    let state_file = args.state_file.clone();
+    let config = args.into_config(&profile)?;
+    config.check_provenance_format()?;
+    if config.is_stdin() {
+        if config.validation.as_ref().is_some_and(|v| v.on_invalid == OnInvalid::Quarantine) {
+            anyhow::bail!("--on-invalid quarantine cannot be used when reading from stdin.");
+        }
+        convert_stdin(&config, input_type)?;
+        return Ok(ExitCode::SUCCESS);
+    }
     if !input_path.exists() {
         anyhow::bail!("Input path '{}' does not exist.", input_path.display());
     }
@@ -91,22 +352,139 @@ fn main() -> Result<()> {
         format!("Failed to create output folder '{}'.", output_folder.display())
     })?;
 
-    if input_path.is_file() {
-        process_file(config, &output_folder)?;
-    } else if input_path.is_dir() {
-        let mut entry_config = config.clone();
-        for entry in fs::read_dir(input_path)? {
-            let entry = entry?;
-            let path = entry.path();
-            if path.is_file() {
-                entry_config.input_path = path;
-                process_file(entry_config.clone(), &output_folder)?;
-            }
+    if incremental && (merge.is_some() || watch) {
+        anyhow::bail!("--incremental cannot be used with --merge or --watch.");
+    }
+    if watch {
+        if !input_path.is_dir() || merge.is_some() {
+            anyhow::bail!("--watch requires an input folder and cannot be used with --merge.");
         }
+        let state_path = state_file.unwrap_or_else(|| output_folder.join(DEFAULT_STATE_FILE));
+        let mut watcher = Watcher::new(
+            &input_path,
+            &output_folder,
+            &config,
+            input_type,
+            recursive,
+            settle_delay,
+            &state_path,
+        )?;
+        println!("Watching '{}' for JSON files...", input_path.display());
+        watcher.run(poll_interval, FileResult::log)?;
+    }
+
+    let inputs = if input_path.is_file() {
+        vec![(input_path.clone(), output_folder.clone())]
+    } else if input_path.is_dir() {
+        collect_input_files(&input_path, recursive)?
+            .into_iter()
+            .map(|path| {
+                let entry_output_folder = mirror_output_folder(&input_path, &path, &output_folder);
+                (path, entry_output_folder)
+            })
+            .collect()
     } else {
         anyhow::bail!("Input path '{}' is neither a file nor a directory.", input_path.display());
+    };
+
+    let (mut results, dangling_references) = match &merge {
+        Some(merge_path) => {
+            let input_paths: Vec<PathBuf> = inputs.into_iter().map(|(path, _)| path).collect();
+            let (merged, results) = merge_files(
+                &input_paths,
+                &config,
+                input_type,
+                jobs,
+                keep_going,
+                merge_path,
+                FileResult::log,
+            )?;
+            if !results.iter().any(FileResult::is_failed) || keep_going {
+                write_merged_graph(&merged, &config, merge_path)?;
+            }
+            let dangling_references: Vec<String> =
+                merged.dangling_references()?.iter().map(ToString::to_string).collect();
+            (results, dangling_references)
+        }
+        None if incremental => {
+            let manifest_path = output_folder.join(MANIFEST_FILE);
+            let mut manifest = Manifest::load(&manifest_path)?;
+            let results = if force {
+                process_files(&inputs, &config, input_type, jobs, keep_going, FileResult::log)
+            } else {
+                manifest.process_files(
+                    &inputs,
+                    &config,
+                    input_type,
+                    jobs,
+                    keep_going,
+                    FileResult::log,
+                )
+            };
+            manifest.record(&results, &config, input_type);
+            manifest.save(&manifest_path)?;
+            (results, Vec::new())
+        }
+        None => {
+            let results =
+                process_files(&inputs, &config, input_type, jobs, keep_going, FileResult::log);
+            (results, Vec::new())
+        }
+    };
+
+    if let Some(report) = report {
+        let mut conversion_report = ConversionReport::from_results(&results);
+        conversion_report.dangling_references = dangling_references.clone();
+        conversion_report.write(&report)?;
     }
 
+    if recursive {
+        print_folder_counts(&results);
+    }
+    print_summary(&results);
+    for dangling_reference in &dangling_references {
+        eprintln!("Warning: dangling reference: {}", dangling_reference);
+    }
+
+    // Without --keep-going, the conversion stops with the error of the first failure.
+    if !keep_going {
+        if let Some(index) = results.iter().position(FileResult::is_failed) {
+            if let Outcome::Failed(err) = results.swap_remove(index).outcome {
+                return Err(err);
+            }
+        }
+    }
+
+    if results.iter().any(FileResult::is_failed) {
+        return Ok(ExitCode::FAILURE);
+    }
     println!("All files processed.");
-    Ok(())
+    Ok(ExitCode::SUCCESS)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use converter::convert::InputTypeOverrides;
+
+    #[test]
+    fn test_materialize_precedence() {
+        let overrides = InputTypeOverrides { materialize: Some(true), ..Default::default() };
+        let profile = Profile {
+            format: Some(RdfFormat::Turtle),
+            materialize: Some(true),
+            input_types: [("agilent".to_string(), overrides)].into(),
+            ..Default::default()
+        };
+        let config = |flags: &[&str]| {
+            let args = Args::parse_from(["converter", "input.json"].iter().chain(flags));
+            let config = args.into_config(&profile).unwrap();
+            (config.materialize, config.for_input_type(InputType::Agilent).materialize)
+        };
+
+        assert_eq!(config(&[]), (true, true));
+        assert_eq!(config(&["--no-materialize"]), (false, false));
+        assert_eq!(config(&["--no-materialize", "--materialize"]), (true, true));
+        assert_eq!(config(&["--materialize", "--no-materialize"]), (false, false));
+    }
 }
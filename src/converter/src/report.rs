@@ -0,0 +1,128 @@
+use crate::{
+    batch::{FileResult, Outcome},
+    io::InputType,
+};
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::{
+    fs::File,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+/// Status of a file in the conversion report.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Converted,
+    Skipped,
+    #[serde(rename = "up-to-date")]
+    UpToDate,
+    Invalid,
+    Failed,
+}
+
+/// Report entry for a single input file.
+#[derive(Serialize, Debug, Clone)]
+pub struct FileReport {
+    pub input_path: PathBuf,
+    pub input_type: Option<InputType>,
+    pub status: Status,
+    pub output_path: Option<PathBuf>,
+    pub triple_count: Option<usize>,
+    pub elapsed_seconds: f64,
+    pub skip_reason: Option<String>,
+    pub error_chain: Option<Vec<String>>,
+    /// Why the graph does not conform to the shapes, with `--validate`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub invalid_reason: Option<String>,
+    /// Validation report of a quarantined output.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub validation_report_path: Option<PathBuf>,
+}
+
+impl From<&FileResult> for FileReport {
+    fn from(result: &FileResult) -> Self {
+        let mut report = FileReport {
+            input_path: result.input_path.clone(),
+            input_type: result.input_type,
+            status: Status::Converted,
+            output_path: None,
+            triple_count: None,
+            elapsed_seconds: result.elapsed.as_secs_f64(),
+            skip_reason: None,
+            error_chain: None,
+            invalid_reason: None,
+            validation_report_path: None,
+        };
+        match &result.outcome {
+            Outcome::Converted { output_path, triple_count } => {
+                report.output_path = Some(output_path.clone());
+                report.triple_count = Some(*triple_count);
+            }
+            Outcome::Skipped(reason) => {
+                report.status = Status::Skipped;
+                report.skip_reason = Some(reason.clone());
+            }
+            Outcome::UpToDate { output_path } => {
+                report.status = Status::UpToDate;
+                report.output_path = Some(output_path.clone());
+            }
+            Outcome::Invalid { output_path, triple_count, report_path, reason } => {
+                report.status = Status::Invalid;
+                report.output_path = Some(output_path.clone());
+                report.triple_count = Some(*triple_count);
+                report.invalid_reason = Some(reason.clone());
+                report.validation_report_path = report_path.clone();
+            }
+            Outcome::Failed(err) => {
+                report.status = Status::Failed;
+                report.error_chain = Some(err.chain().map(|cause| cause.to_string()).collect());
+            }
+        }
+        report
+    }
+}
+
+/// Machine-readable report of a conversion run.
+#[derive(Serialize, Debug, Clone)]
+pub struct ConversionReport {
+    pub converted: usize,
+    pub skipped: usize,
+    /// Files not converted again with `--incremental`, since their output is up to date.
+    pub up_to_date: usize,
+    /// Files whose graph does not conform to the shapes, with `--validate`.
+    pub invalid: usize,
+    pub failed: usize,
+    pub files: Vec<FileReport>,
+    /// References that do not resolve between the merged files, with `--merge`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub dangling_references: Vec<String>,
+}
+
+impl ConversionReport {
+    pub fn from_results(results: &[FileResult]) -> Self {
+        let files: Vec<FileReport> = results.iter().map(FileReport::from).collect();
+        let count = |status| files.iter().filter(|file| file.status == status).count();
+
+        ConversionReport {
+            converted: count(Status::Converted),
+            skipped: count(Status::Skipped),
+            up_to_date: count(Status::UpToDate),
+            invalid: count(Status::Invalid),
+            failed: count(Status::Failed),
+            files,
+            dangling_references: Vec::new(),
+        }
+    }
+
+    /// Writes the report as pretty-printed JSON to `path`.
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let content = serde_json::to_string_pretty(self).context("Failed to serialize report")?;
+        File::create(path)
+            .with_context(|| format!("Failed to create report file '{}'.", path.display()))?
+            .write_all(content.as_bytes())
+            .with_context(|| format!("Failed to write report file '{}'.", path.display()))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,118 @@
+use anyhow::{anyhow, Context, Result};
+use catplus_common::{
+    graph::utils::sha256_hex,
+    rdf::rdf_parser::{parse_to_graph, RdfSyntax},
+};
+use serde::Deserialize;
+use sophia::inmem::graph::LightGraph;
+use sophia_api::term::Term;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use validation::{
+    core::{Severity, ShaclEngine, ValidationReport},
+    engines::{native::NativeShaclEngine, shacl_api::ShaclApiEndpoint},
+    report::{render_report, summary_line, ReportFormat},
+};
+
+/// Name of the folder, in the output folder, of the outputs quarantined by [OnInvalid::Quarantine].
+pub const QUARANTINE_FOLDER: &str = "quarantine";
+
+/// What to do with the output of a file whose graph does not conform to the shapes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum OnInvalid {
+    /// Do not write the output, and fail the file.
+    Fail,
+    /// Write the output as usual, and report the file as invalid.
+    Warn,
+    /// Write the output and its validation report to the quarantine folder.
+    Quarantine,
+}
+
+/// Validation of the graphs before they are written, with `--validate`.
+#[derive(Clone, Debug)]
+pub struct ValidationConfig {
+    /// Shapes evaluated by the native engine, if no endpoint is given.
+    pub shapes: Option<Arc<LightGraph>>,
+    /// Hex-encoded SHA-256 digest of the shapes file, so that changed shapes invalidate outputs.
+    pub shapes_sha256: Option<String>,
+    /// Endpoint of a SHACL API server, which uses its own shapes if none are given.
+    pub endpoint: Option<String>,
+    pub on_invalid: OnInvalid,
+}
+
+impl ValidationConfig {
+    /// Reads the shapes at `shapes_path` and checks that an engine can validate with them.
+    pub fn new(
+        shapes_path: Option<&Path>,
+        endpoint: Option<String>,
+        on_invalid: OnInvalid,
+    ) -> Result<Self> {
+        let (shapes, shapes_sha256) = match shapes_path {
+            Some(path) => {
+                let content = fs::read_to_string(path)
+                    .with_context(|| format!("Failed to read shapes file '{}'.", path.display()))?;
+                let shapes =
+                    parse_to_graph(&content, RdfSyntax::from_path(path)).with_context(|| {
+                        format!("Failed to parse shapes file '{}'.", path.display())
+                    })?;
+                (Some(Arc::new(shapes)), Some(sha256_hex(content.as_bytes())))
+            }
+            None => (None, None),
+        };
+        match &endpoint {
+            Some(endpoint) if !ShaclApiEndpoint::new(endpoint.clone()).is_available() => {
+                anyhow::bail!("SHACL API is not available at {}", endpoint)
+            }
+            None if shapes.is_none() => {
+                anyhow::bail!("--validate requires --shapes, or the --shacl-endpoint of a server.")
+            }
+            _ => {}
+        }
+        Ok(ValidationConfig { shapes, shapes_sha256, endpoint, on_invalid })
+    }
+
+    /// Validates `graph` against the shapes.
+    pub fn validate(&self, graph: &LightGraph) -> Result<ValidationReport> {
+        let shapes = self.shapes.as_deref();
+        let report = match &self.endpoint {
+            Some(endpoint) => ShaclApiEndpoint::new(endpoint.clone()).validate(graph, shapes),
+            None => NativeShaclEngine::new().validate(graph, shapes),
+        };
+        report.map_err(|err| anyhow!(err)).context("Failed to validate the graph")
+    }
+}
+
+/// Describes why a graph does not conform, with the counts and the first result.
+pub fn nonconformance(report: &ValidationReport) -> String {
+    let mut description =
+        format!("Graph does not conform to the shapes ({})", summary_line(report));
+    if let Some(result) = report.results.first() {
+        if let Some(message) = &result.message {
+            description.push_str(&format!(", first: {}", message));
+        }
+        if let Some(path) = result.result_path.as_ref().and_then(|path| path.iri()) {
+            description.push_str(&format!(" (path <{}>)", path.as_str()));
+        }
+    }
+    description
+}
+
+/// Writes the validation report of the output at `output_path` next to it, in turtle.
+/// Returns the path of the report.
+pub fn save_validation_report(
+    report: &ValidationReport,
+    graph: &LightGraph,
+    output_path: &Path,
+) -> Result<PathBuf> {
+    let stem = output_path.file_stem().and_then(|s| s.to_str()).context("Invalid file stem")?;
+    let report_path = output_path.with_file_name(format!("{}.validation.ttl", stem));
+    let content = render_report(report, ReportFormat::Turtle, Severity::Violation, graph, None)?;
+    fs::write(&report_path, content).with_context(|| {
+        format!("Failed to write validation report '{}'.", report_path.display())
+    })?;
+    Ok(report_path)
+}
@@ -0,0 +1,180 @@
+use crate::{
+    batch::{process_file, FileResult, Outcome},
+    convert::ConverterConfig,
+    io::{collect_input_files, mirror_output_folder, InputType},
+};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, SystemTime},
+};
+
+/// Name of the state file written in the output folder if no path is given.
+pub const DEFAULT_STATE_FILE: &str = ".catplus-watch-state.json";
+
+/// Version of a watched file that was processed, and how.
+///
+/// Files that were skipped or failed are processed again only once they are modified.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FileState {
+    pub modified: SystemTime,
+    pub size: u64,
+    /// Output path if the file was converted, or the reason it was skipped or failed.
+    pub outcome: String,
+}
+
+/// Files processed by a [Watcher], persisted so that restarts do not convert them again.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct WatchState {
+    pub files: BTreeMap<PathBuf, FileState>,
+}
+
+impl WatchState {
+    /// Reads the state file at `path`, or returns an empty state if it does not exist.
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read state file '{}'", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Invalid state file '{}'", path.display()))
+    }
+
+    /// Writes the state file at `path`, replacing it at once so that it is never half-written.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let content = serde_json::to_string_pretty(self).context("Failed to serialize state")?;
+        let temporary_path = path.with_extension("tmp");
+        fs::write(&temporary_path, content)
+            .with_context(|| format!("Failed to write state file '{}'", path.display()))?;
+        fs::rename(&temporary_path, path)
+            .with_context(|| format!("Failed to write state file '{}'", path.display()))
+    }
+}
+
+/// Converts the JSON files dropped in a folder as they arrive.
+///
+/// The folder is polled rather than subscribed to, since shared folders on network
+/// filesystems often do not deliver change events. New or modified files are processed
+/// with [process_file] once they were not modified for the settle delay, so that files
+/// still being written are left alone.
+pub struct Watcher {
+    pub input_folder: PathBuf,
+    pub output_folder: PathBuf,
+    pub config: ConverterConfig,
+    pub input_type: Option<InputType>,
+    pub recursive: bool,
+    pub settle_delay: Duration,
+    pub state_path: PathBuf,
+    pub state: WatchState,
+}
+
+impl Watcher {
+    /// Creates a watcher of `input_folder`, resuming from the state file at `state_path`.
+    pub fn new(
+        input_folder: &Path,
+        output_folder: &Path,
+        config: &ConverterConfig,
+        input_type: Option<InputType>,
+        recursive: bool,
+        settle_delay: Duration,
+        state_path: &Path,
+    ) -> Result<Self> {
+        Ok(Self {
+            input_folder: input_folder.to_path_buf(),
+            output_folder: output_folder.to_path_buf(),
+            config: config.clone(),
+            input_type,
+            recursive,
+            settle_delay,
+            state_path: state_path.to_path_buf(),
+            state: WatchState::load(state_path)?,
+        })
+    }
+
+    /// Processes the settled files that are new or modified since they were last processed.
+    ///
+    /// The results are passed to `on_result`, and the state file is updated after each file.
+    pub fn poll<F>(&mut self, mut on_result: F) -> Result<Vec<FileResult>>
+    where
+        F: FnMut(&FileResult),
+    {
+        let mut results = Vec::new();
+        for input_path in collect_input_files(&self.input_folder, self.recursive)? {
+            if input_path.extension().and_then(|extension| extension.to_str()) != Some("json")
+                || input_path.file_name().is_some_and(|name| name == DEFAULT_STATE_FILE)
+                || input_path == self.state_path
+            {
+                continue;
+            }
+            // The file may have been removed since it was listed.
+            let Ok(metadata) = fs::metadata(&input_path) else {
+                continue;
+            };
+            let modified = metadata.modified()?;
+            let size = metadata.len();
+            if self
+                .state
+                .files
+                .get(&input_path)
+                .is_some_and(|state| state.modified == modified && state.size == size)
+            {
+                continue;
+            }
+            let age = SystemTime::now().duration_since(modified).unwrap_or_default();
+            if age < self.settle_delay {
+                continue;
+            }
+
+            let output_folder = if self.recursive {
+                mirror_output_folder(&self.input_folder, &input_path, &self.output_folder)
+            } else {
+                self.output_folder.clone()
+            };
+            fs::create_dir_all(&output_folder).with_context(|| {
+                format!("Failed to create output folder '{}'.", output_folder.display())
+            })?;
+            let mut file_config = self.config.clone();
+            file_config.input_path = input_path.clone();
+            let result = process_file(&file_config, &output_folder, self.input_type);
+            on_result(&result);
+
+            let outcome = match &result.outcome {
+                Outcome::Converted { output_path, .. } | Outcome::UpToDate { output_path } => {
+                    output_path.display().to_string()
+                }
+                Outcome::Invalid { output_path, reason, .. } => {
+                    format!("invalid: {} ({})", output_path.display(), reason)
+                }
+                Outcome::Skipped(reason) => format!("skipped: {}", reason),
+                Outcome::Failed(err) => format!("failed: {:#}", err),
+            };
+            self.state.files.insert(input_path, FileState { modified, size, outcome });
+            self.state.save(&self.state_path)?;
+            results.push(result);
+        }
+        Ok(results)
+    }
+
+    /// Polls the folder every `poll_interval`, until the process is stopped.
+    ///
+    /// Errors of the first poll are returned, as they come from the setup, such as an
+    /// unreadable folder or state file. Later errors, such as a network share that is
+    /// briefly unavailable, are logged and the folder is polled again at the next interval.
+    pub fn run<F>(&mut self, poll_interval: Duration, mut on_result: F) -> Result<()>
+    where
+        F: FnMut(&FileResult),
+    {
+        self.poll(&mut on_result)?;
+        loop {
+            thread::sleep(poll_interval);
+            if let Err(err) = self.poll(&mut on_result) {
+                eprintln!("Failed to poll '{}': {:#}", self.input_folder.display(), err);
+            }
+        }
+    }
+}
@@ -1,6 +1,10 @@
 use catplus_common::{
+    graph::skolem::{deskolemize_graph, GENID_PATH},
     models::agilent::LiquidChromatographyAggregateDocumentWrapper,
-    rdf::rdf_parser::parse_turtle_to_graph,
+    rdf::{
+        rdf_diff::diff_graphs,
+        rdf_parser::{parse_to_graph, parse_turtle_to_graph, RdfSyntax},
+    },
 };
 use converter::convert::json_to_rdf;
 use sophia_isomorphism::isomorphic_graphs;
@@ -11,8 +15,22 @@ use common::get_test_config;
 #[test]
 fn test_materialize_blank_nodes() {
     let mut config = get_test_config("data/tests/agilent_blank_nodes.json");
+    let unskolemized =
+        json_to_rdf::<LiquidChromatographyAggregateDocumentWrapper>(&config).unwrap();
     config.materialize = true;
-    let _ = json_to_rdf::<LiquidChromatographyAggregateDocumentWrapper>(&config);
+    let materialized =
+        json_to_rdf::<LiquidChromatographyAggregateDocumentWrapper>(&config).unwrap();
+    assert!(materialized.contains(GENID_PATH));
+
+    // Re-reading the materialized output gives back the blank nodes.
+    let reread = parse_to_graph(&materialized, Some(RdfSyntax::Turtle)).unwrap();
+    let deskolemized = deskolemize_graph(&reread).unwrap();
+    let expected = parse_turtle_to_graph(&unskolemized).unwrap();
+    assert!(
+        isomorphic_graphs(&expected, &deskolemized).unwrap(),
+        "{}",
+        diff_graphs(&expected, &deskolemized).unwrap()
+    );
 }
 
 #[test]
@@ -152,7 +170,7 @@ fn test_convert_liquid_chromatography() {
     println!("Result Turtle:\n{}", result_ttl);
     let result_graph = parse_turtle_to_graph(&result_ttl).unwrap();
     let graphs_match = isomorphic_graphs(&result_graph, &expected_graph);
-    assert_eq!(graphs_match.unwrap(), true);
+    assert!(graphs_match.unwrap(), "{}", diff_graphs(&expected_graph, &result_graph).unwrap());
 }
 
 #[test]
@@ -208,5 +226,5 @@ fn test_convert_device_system_document() {
     let result_ttl = result.as_ref().unwrap().as_str();
     let result_graph = parse_turtle_to_graph(&result_ttl).unwrap();
     let graphs_match = isomorphic_graphs(&result_graph, &expected_graph);
-    assert_eq!(graphs_match.unwrap(), true);
+    assert!(graphs_match.unwrap(), "{}", diff_graphs(&expected_graph, &result_graph).unwrap());
 }
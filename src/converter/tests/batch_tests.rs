@@ -0,0 +1,191 @@
+use catplus_common::graph::references::DanglingReference;
+use converter::{
+    batch::{build_graph_from_str, merge_files, process_file, process_files, Outcome},
+    io::InputType,
+    report::{ConversionReport, Status},
+};
+use sophia_api::graph::Graph;
+use std::{fs, path::PathBuf};
+
+mod common;
+use common::{get_data_path, get_test_config};
+
+#[test]
+fn test_process_file_outcomes() {
+    let output_folder = std::env::temp_dir().join("catplus_batch_tests_outcomes");
+    fs::create_dir_all(&output_folder).unwrap();
+
+    let config = get_test_config("data/tests/hci_campaign.json");
+    let result = process_file(&config, &output_folder, None);
+    assert_eq!(result.input_type, Some(InputType::HCI));
+    match result.outcome {
+        Outcome::Converted { output_path, .. } => {
+            assert_eq!(output_path, output_folder.join("hci_campaign.ttl"));
+            assert!(output_path.exists());
+        }
+        outcome => panic!("Unexpected outcome: {:?}", outcome),
+    }
+
+    let config = get_test_config("README.md");
+    let result = process_file(&config, &output_folder, None);
+    assert!(matches!(result.outcome, Outcome::Skipped(_)));
+
+    // Forcing the wrong input type makes the conversion fail.
+    let config = get_test_config("data/tests/hci_campaign.json");
+    let result = process_file(&config, &output_folder, Some(InputType::Synth));
+    assert!(result.is_failed());
+}
+
+#[test]
+fn test_conversion_report() {
+    let output_folder = std::env::temp_dir().join("catplus_batch_tests_report");
+    fs::create_dir_all(&output_folder).unwrap();
+
+    let results = vec![
+        process_file(&get_test_config("data/tests/synth_add_action.json"), &output_folder, None),
+        process_file(&get_test_config("README.md"), &output_folder, None),
+        process_file(
+            &get_test_config("data/tests/synth_add_action.json"),
+            &output_folder,
+            Some(InputType::HCI),
+        ),
+    ];
+    let report = ConversionReport::from_results(&results);
+    assert_eq!((report.converted, report.skipped, report.failed), (1, 1, 1));
+
+    let converted = &report.files[0];
+    assert_eq!(converted.status, Status::Converted);
+    assert_eq!(converted.input_type, Some(InputType::Synth));
+    assert!(converted.triple_count.unwrap() > 0);
+
+    let failed = &report.files[2];
+    assert_eq!(failed.status, Status::Failed);
+    assert!(failed.error_chain.as_ref().unwrap().len() > 1);
+
+    let report_path = output_folder.join("report.json");
+    report.write(&report_path).unwrap();
+    let written: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(report_path).unwrap()).unwrap();
+    assert_eq!(written["files"][1]["status"], "skipped");
+}
+
+#[test]
+fn test_process_files_in_parallel() {
+    let output_folder = std::env::temp_dir().join("catplus_batch_tests_parallel");
+    let inputs: Vec<(PathBuf, PathBuf)> = [
+        "data/tests/synth_add_action.json",
+        "data/tests/hci_campaign.json",
+        "README.md",
+        "data/tests/bravo1_add_action.json",
+        "data/tests/agilent_device_system_document.json",
+    ]
+    .iter()
+    .map(|path| (get_data_path(path), output_folder.clone()))
+    .collect();
+    let config = get_test_config("data/tests/synth_add_action.json");
+
+    let mut logged = Vec::new();
+    let results = process_files(&inputs, &config, None, 4, true, |result| {
+        logged.push(result.input_path.clone())
+    });
+
+    let input_paths: Vec<PathBuf> = inputs.into_iter().map(|(path, _)| path).collect();
+    assert_eq!(results.iter().map(|r| r.input_path.clone()).collect::<Vec<_>>(), input_paths);
+    assert_eq!(logged, input_paths);
+    assert!(results.iter().all(|result| !result.is_failed()));
+}
+
+#[test]
+fn test_process_files_stops_at_failure() {
+    let output_folder = std::env::temp_dir().join("catplus_batch_tests_stop");
+    let inputs: Vec<(PathBuf, PathBuf)> = [
+        "data/tests/synth_add_action.json",
+        "data/tests/hci_campaign.json",
+        "data/tests/synth_shake_action.json",
+    ]
+    .iter()
+    .map(|path| (get_data_path(path), output_folder.clone()))
+    .collect();
+    let config = get_test_config("data/tests/synth_add_action.json");
+
+    let results = process_files(&inputs, &config, Some(InputType::Synth), 1, false, |_| {});
+    assert_eq!(results.len(), 2);
+    assert!(results[1].is_failed());
+}
+
+#[test]
+fn test_process_files_reports_running_files_after_failure() {
+    let output_folder = std::env::temp_dir().join("catplus_batch_tests_stop_running");
+    let _ = fs::remove_dir_all(&output_folder);
+    let inputs: Vec<(PathBuf, PathBuf)> = [
+        "data/tests/hci_campaign.json",
+        "data/tests/synth_add_action.json",
+        "data/tests/synth_filtrate_action.json",
+        "data/tests/synth_set_pressure_action.json",
+        "data/tests/synth_set_temperature_action.json",
+        "data/tests/synth_shake_action.json",
+    ]
+    .iter()
+    .map(|path| (get_data_path(path), output_folder.clone()))
+    .collect();
+    let config = get_test_config("data/tests/synth_add_action.json");
+
+    let results = process_files(&inputs, &config, Some(InputType::Synth), 4, false, |_| {});
+    assert!(results[0].is_failed());
+    // Every written output is reported, even if it completed after the failure.
+    let mut reported: Vec<PathBuf> = results
+        .iter()
+        .filter_map(|result| match &result.outcome {
+            Outcome::Converted { output_path, .. } => Some(output_path.clone()),
+            _ => None,
+        })
+        .collect();
+    let mut written: Vec<PathBuf> = fs::read_dir(&output_folder)
+        .map(|entries| entries.map(|entry| entry.unwrap().path()).collect())
+        .unwrap_or_default();
+    reported.sort();
+    written.sort();
+    assert_eq!(reported, written);
+}
+
+#[test]
+fn test_build_graph_from_stdin_content() {
+    let content = fs::read_to_string(get_data_path("data/tests/synth_add_action.json")).unwrap();
+    let mut config = get_test_config("data/tests/synth_add_action.json");
+    config.input_path = PathBuf::from("-");
+    assert!(build_graph_from_str(&content, &config, InputType::Synth).is_err());
+
+    config.content_uri = Some("https://example.org/stdin.json".to_string());
+    let graph_builder = build_graph_from_str(&content, &config, InputType::Synth).unwrap();
+    assert_eq!(graph_builder.content_url.as_deref(), Some("https://example.org/stdin.json"));
+}
+
+#[test]
+fn test_merge_files() {
+    let output_path = std::env::temp_dir().join("catplus_batch_tests_merge/merged.ttl");
+    let config = get_test_config("data/tests/synth_add_action.json");
+    let synth = get_data_path("data/tests/synth_add_action.json");
+    let agilent = get_data_path("data/tests/agilent_liquid_chromatography_aggregate_document.json");
+
+    let (single, _) =
+        merge_files(std::slice::from_ref(&synth), &config, None, 1, false, &output_path, |_| {})
+            .unwrap();
+    let (twice, results) =
+        merge_files(&[synth.clone(), synth.clone()], &config, None, 2, false, &output_path, |_| {})
+            .unwrap();
+    // Identical resources are unified.
+    assert_eq!(twice.graph.triples().count(), single.graph.triples().count());
+    assert!(
+        matches!(&results[1].outcome, Outcome::Converted { output_path: path, .. } if *path == output_path)
+    );
+    assert!(single.dangling_references().unwrap().is_empty());
+
+    let (merged, _) =
+        merge_files(&[synth, agilent], &config, None, 1, false, &output_path, |_| {}).unwrap();
+    let dangling = merged.dangling_references().unwrap();
+    assert!(dangling.iter().any(|reference| matches!(
+        reference,
+        DanglingReference::UnproducedProduct { identifier: Some(identifier), .. }
+            if identifier.ends_with(".dx")
+    )));
+}
@@ -1,15 +1,28 @@
+use catplus_common::graph::iri_base::IriBase;
 use converter::convert::{ConverterConfig, RdfFormat};
-use std::path::{Path, PathBuf};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
 
 pub(crate) fn get_data_path(path: &str) -> PathBuf {
     Path::new("../../").join(path)
 }
 
+#[allow(dead_code)]
 pub(crate) fn get_test_config(input_path: &str) -> ConverterConfig {
     ConverterConfig {
         input_path: get_data_path(input_path),
         format: RdfFormat::Turtle,
         prefix: Some("http://example.org/test/".to_string()),
         materialize: false,
+        random_bnodes: false,
+        context_url: None,
+        frame: None,
+        content_uri: None,
+        provenance: None,
+        iri_base: IriBase::default(),
+        input_type_overrides: HashMap::new(),
+        validation: None,
     }
 }
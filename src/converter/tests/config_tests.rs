@@ -0,0 +1,74 @@
+use converter::{
+    config_file::{load_profile, ConfigFile},
+    convert::RdfFormat,
+    io::InputType,
+};
+use std::{fs, path::Path};
+
+mod common;
+use common::get_test_config;
+
+const CONFIG: &str = r#"
+[profiles.default]
+format = "turtle"
+output-folder = "rdf"
+prefix = "https://data.example.org/raw/"
+resource-base = "https://data.example.org/resource/"
+materialize = true
+
+[profiles.default.input-types.agilent]
+format = "trig"
+materialize = false
+
+[profiles.archive]
+format = "nquads"
+"#;
+
+#[test]
+fn test_parse_config_file() {
+    let config_file = ConfigFile::parse(CONFIG).unwrap();
+    let profile = config_file.profile("default").unwrap();
+    assert!(matches!(profile.format, Some(RdfFormat::Turtle)));
+    assert_eq!(profile.prefix.as_deref(), Some("https://data.example.org/raw/"));
+    assert_eq!(profile.materialize, Some(true));
+
+    let overrides = profile.input_type_overrides().unwrap();
+    assert!(matches!(overrides[&InputType::Agilent].format, Some(RdfFormat::Trig)));
+
+    assert!(matches!(config_file.profile("archive").unwrap().format, Some(RdfFormat::NQuads)));
+    assert!(config_file.profile("missing").is_err());
+    assert!(ConfigFile::parse("[profiles.default]\nformats = \"turtle\"").is_err());
+}
+
+#[test]
+fn test_load_profile() {
+    let folder = std::env::temp_dir().join("catplus_config_tests");
+    fs::create_dir_all(&folder).unwrap();
+    let config_path = folder.join("catplus.toml");
+    fs::write(&config_path, CONFIG).unwrap();
+
+    let profile = load_profile(Some(&config_path), None).unwrap();
+    assert_eq!(profile.output_folder, Some(folder.join("rdf")));
+    assert!(matches!(
+        load_profile(Some(&config_path), Some("archive")).unwrap().format,
+        Some(RdfFormat::NQuads)
+    ));
+    assert!(load_profile(Some(&config_path), Some("missing")).is_err());
+    assert!(load_profile(Some(Path::new("missing.toml")), None).is_err());
+}
+
+#[test]
+fn test_input_type_overrides() {
+    let profile = ConfigFile::parse(CONFIG).unwrap().profile("default").unwrap().clone();
+    let mut config = get_test_config("data/tests/agilent_device_system_document.json");
+    config.materialize = true;
+    config.input_type_overrides = profile.input_type_overrides().unwrap();
+
+    let agilent_config = config.for_input_type(InputType::Agilent);
+    assert!(matches!(agilent_config.format, RdfFormat::Trig));
+    assert!(!agilent_config.materialize);
+
+    let synth_config = config.for_input_type(InputType::Synth);
+    assert!(matches!(synth_config.format, RdfFormat::Turtle));
+    assert!(synth_config.materialize);
+}
@@ -0,0 +1,186 @@
+use catplus_common::{
+    graph::iri_base::IriBase,
+    models::{
+        agilent::LiquidChromatographyAggregateDocumentWrapper, hci::CampaignWrapper,
+        synth::SynthBatch,
+    },
+    rdf::rdf_parser::parse_turtle_to_graph,
+};
+use converter::convert::{json_to_graph, json_to_rdf, JsonLdFrame, RdfFormat};
+use sophia::{
+    api::prelude::*, inmem::graph::LightGraph, jsonld::JsonLdParser, xml::parser::RdfXmlParser,
+};
+use sophia_isomorphism::isomorphic_graphs;
+use std::fs;
+
+mod common;
+use common::{get_data_path, get_test_config};
+
+#[test]
+fn test_convert_to_ntriples() {
+    let mut config = get_test_config("data/tests/hci_campaign.json");
+    let turtle = json_to_rdf::<CampaignWrapper>(&config).unwrap();
+    config.format = RdfFormat::NTriples;
+    let ntriples = json_to_rdf::<CampaignWrapper>(&config).unwrap();
+
+    // N-Triples is a subset of Turtle
+    let turtle_graph = parse_turtle_to_graph(&turtle).unwrap();
+    let ntriples_graph = parse_turtle_to_graph(&ntriples).unwrap();
+    assert!(isomorphic_graphs(&turtle_graph, &ntriples_graph).unwrap());
+    assert!(ntriples.lines().all(|line| line.ends_with('.')));
+}
+
+#[test]
+fn test_convert_to_nquads_named_graph() {
+    let mut config = get_test_config("data/tests/hci_campaign.json");
+    config.format = RdfFormat::NQuads;
+    let nquads = json_to_rdf::<CampaignWrapper>(&config).unwrap();
+
+    let graph_name = "<http://example.org/test/../../data/tests/hci_campaign.json>.";
+    assert!(nquads.lines().count() > 0);
+    assert!(nquads.lines().all(|line| line.ends_with(graph_name)));
+}
+
+#[test]
+fn test_convert_path_with_space_to_named_graph() {
+    let folder = std::env::temp_dir().join("catplus_format_tests/my batch");
+    fs::create_dir_all(&folder).unwrap();
+    let input_path = folder.join("hci_campaign.json");
+    fs::copy(get_data_path("data/tests/hci_campaign.json"), &input_path).unwrap();
+    let mut config = get_test_config("data/tests/hci_campaign.json");
+    config.input_path = input_path;
+
+    let graph_name = format!("<file://{}/hci_campaign.json>", folder.display()).replace(' ', "%20");
+    config.format = RdfFormat::NQuads;
+    let nquads = json_to_rdf::<CampaignWrapper>(&config).unwrap();
+    assert!(nquads.lines().all(|line| line.ends_with(&format!("{}.", graph_name))));
+    config.format = RdfFormat::Trig;
+    let trig = json_to_rdf::<CampaignWrapper>(&config).unwrap();
+    assert!(trig.contains(&format!("{} {{", graph_name)));
+}
+
+#[test]
+fn test_convert_to_rdfxml() {
+    let mut config = get_test_config("data/tests/hci_campaign.json");
+    let turtle = json_to_rdf::<CampaignWrapper>(&config).unwrap();
+    config.format = RdfFormat::RdfXml;
+    let rdfxml = json_to_rdf::<CampaignWrapper>(&config).unwrap();
+
+    assert!(rdfxml.contains(r#"xmlns:cat="http://example.org/catplus/ontology/""#));
+    assert!(rdfxml.contains("<cat:campaignClass>Standard Research</cat:campaignClass>"));
+
+    let turtle_graph = parse_turtle_to_graph(&turtle).unwrap();
+    let mut rdfxml_graph = LightGraph::new();
+    RdfXmlParser::default().parse_str(&rdfxml).add_to_graph(&mut rdfxml_graph).unwrap();
+    assert!(isomorphic_graphs(&turtle_graph, &rdfxml_graph).unwrap());
+}
+
+#[test]
+fn test_convert_to_trig_named_graph() {
+    let mut config = get_test_config("data/tests/hci_campaign.json");
+    config.format = RdfFormat::Trig;
+    let trig = json_to_rdf::<CampaignWrapper>(&config).unwrap();
+
+    assert!(trig.contains("PREFIX cat: <http://example.org/catplus/ontology/>"));
+    assert!(trig.contains("<http://example.org/test/../../data/tests/hci_campaign.json> {"));
+}
+
+#[test]
+fn test_convert_with_iri_base() {
+    let mut config = get_test_config("data/tests/hci_campaign.json");
+    let default_turtle = json_to_rdf::<CampaignWrapper>(&config).unwrap();
+    assert!(default_turtle.contains("PREFIX cat: <http://example.org/catplus/ontology/>"));
+    assert!(default_turtle.contains("<http://example.org/cat/resource/"));
+
+    config.iri_base = IriBase::new(
+        Some("https://data.example.com/resource/"),
+        Some("https://w3id.org/catplus/ontology/"),
+    )
+    .unwrap();
+    let turtle = json_to_rdf::<CampaignWrapper>(&config).unwrap();
+    assert!(turtle.contains("PREFIX cat: <https://w3id.org/catplus/ontology/>"));
+    assert!(turtle.contains("<https://data.example.com/resource/"));
+    assert!(!turtle.contains("http://example.org/cat"));
+
+    // Only the namespaces change, so that the hashed identifiers still match.
+    let default_graph = parse_turtle_to_graph(&default_turtle).unwrap();
+    let rebased = config.iri_base.rebase_graph(&default_graph).unwrap();
+    let turtle_graph = parse_turtle_to_graph(&turtle).unwrap();
+    assert!(isomorphic_graphs(&rebased, &turtle_graph).unwrap());
+}
+
+fn parse_jsonld(jsonld: &str) -> LightGraph {
+    let mut graph = LightGraph::new();
+    JsonLdParser::new()
+        .parse_str(jsonld)
+        .for_each_quad(|quad| {
+            let (spo, _) = quad.spog();
+            graph.insert_triple(spo).unwrap();
+        })
+        .unwrap();
+    graph
+}
+
+#[test]
+fn test_convert_to_compact_jsonld() {
+    let mut config = get_test_config("data/tests/hci_campaign.json");
+    let turtle = json_to_rdf::<CampaignWrapper>(&config).unwrap();
+    let turtle_graph = parse_turtle_to_graph(&turtle).unwrap();
+
+    config.format = RdfFormat::JsonldCompact;
+    let compact = json_to_rdf::<CampaignWrapper>(&config).unwrap();
+    let document: serde_json::Value = serde_json::from_str(&compact).unwrap();
+    assert_eq!(document["@context"]["cat"], "http://example.org/catplus/ontology/");
+    assert!(compact.contains(r#""@type": "cat:Campaign""#));
+    assert!(isomorphic_graphs(&turtle_graph, &parse_jsonld(&compact)).unwrap());
+
+    config.frame = Some(JsonLdFrame::Campaign);
+    let framed = json_to_rdf::<CampaignWrapper>(&config).unwrap();
+    let document: serde_json::Value = serde_json::from_str(&framed).unwrap();
+    let roots = document["@graph"].as_array().unwrap();
+    assert_eq!(roots.len(), 1);
+    assert_eq!(roots[0]["cat:hasBatch"]["@type"], "cat:Batch");
+    assert!(isomorphic_graphs(&turtle_graph, &parse_jsonld(&framed)).unwrap());
+
+    config.context_url = Some("https://example.org/catplus/context.jsonld".to_string());
+    let referenced = json_to_rdf::<CampaignWrapper>(&config).unwrap();
+    let document: serde_json::Value = serde_json::from_str(&referenced).unwrap();
+    assert_eq!(document["@context"], "https://example.org/catplus/context.jsonld");
+}
+
+#[test]
+fn test_deterministic_bnodes() {
+    let mut config = get_test_config("data/tests/agilent_device_system_document.json");
+    config.materialize = true;
+    let first = json_to_rdf::<LiquidChromatographyAggregateDocumentWrapper>(&config).unwrap();
+    let second = json_to_rdf::<LiquidChromatographyAggregateDocumentWrapper>(&config).unwrap();
+    assert_eq!(first, second);
+
+    config.random_bnodes = true;
+    let random = json_to_rdf::<LiquidChromatographyAggregateDocumentWrapper>(&config).unwrap();
+    assert_ne!(first, random);
+    let first_graph = parse_turtle_to_graph(&first).unwrap();
+    assert_eq!(
+        first_graph.triples().count(),
+        parse_turtle_to_graph(&random).unwrap().triples().count()
+    );
+}
+
+#[test]
+fn test_canonical_output() {
+    let mut config = get_test_config("data/tests/synth_add_action.json");
+    config.format = RdfFormat::Canonical;
+    let canonical = json_to_rdf::<SynthBatch>(&config).unwrap();
+    config.random_bnodes = true;
+    assert_eq!(json_to_rdf::<SynthBatch>(&config).unwrap(), canonical);
+
+    let mut lines: Vec<&str> = canonical.lines().collect();
+    assert!(lines.iter().any(|line| line.starts_with("_:c14n0 ")));
+    lines.sort();
+    assert_eq!(lines, canonical.lines().collect::<Vec<_>>());
+
+    let digest = json_to_graph::<SynthBatch>(&config).unwrap().canonical_digest().unwrap();
+    config.random_bnodes = false;
+    assert_eq!(json_to_graph::<SynthBatch>(&config).unwrap().canonical_digest().unwrap(), digest);
+    assert_eq!(digest.len(), 64);
+}
@@ -1,4 +1,7 @@
-use catplus_common::{models::hci::CampaignWrapper, rdf::rdf_parser::parse_turtle_to_graph};
+use catplus_common::{
+    models::hci::CampaignWrapper,
+    rdf::{rdf_diff::diff_graphs, rdf_parser::parse_turtle_to_graph},
+};
 use converter::convert::json_to_rdf;
 use sophia_isomorphism::isomorphic_graphs;
 
@@ -125,5 +128,5 @@ fn test_convert_campaign() {
     let result_ttl = result.as_ref().unwrap().as_str();
     let result_graph = parse_turtle_to_graph(&result_ttl).unwrap();
     let graphs_match = isomorphic_graphs(&result_graph, &expected_graph);
-    assert_eq!(graphs_match.unwrap(), true);
+    assert!(graphs_match.unwrap(), "{}", diff_graphs(&expected_graph, &result_graph).unwrap());
 }
@@ -0,0 +1,100 @@
+use converter::{
+    batch::Outcome,
+    convert::{ConverterConfig, RdfFormat},
+    incremental::{Manifest, MANIFEST_FILE},
+    io::InputType,
+};
+use std::{fs, path::PathBuf};
+
+mod common;
+use common::{get_data_path, get_test_config};
+
+#[test]
+fn test_incremental_skips_up_to_date_files() {
+    let root = std::env::temp_dir().join("catplus_incremental_tests");
+    let _ = fs::remove_dir_all(&root);
+    fs::create_dir_all(&root).unwrap();
+    let campaign = fs::read_to_string(get_data_path("data/tests/hci_campaign.json")).unwrap();
+    let input_path = root.join("campaign.json");
+    fs::write(&input_path, &campaign).unwrap();
+    let inputs = vec![(input_path.clone(), root.clone())];
+    let manifest_path = root.join(MANIFEST_FILE);
+    let is_up_to_date = |manifest: &Manifest, config: &ConverterConfig| {
+        let results = manifest.process_files(&inputs, config, None, 1, false, |_| {});
+        matches!(results[0].outcome, Outcome::UpToDate { .. })
+    };
+
+    let mut config = get_test_config("data/tests/hci_campaign.json");
+    let mut manifest = Manifest::load(&manifest_path).unwrap();
+    let results = manifest.process_files(&inputs, &config, None, 1, false, |_| {});
+    assert!(matches!(results[0].outcome, Outcome::Converted { .. }));
+    manifest.record(&results, &config, None);
+    manifest.save(&manifest_path).unwrap();
+    assert!(!manifest_path.with_extension("tmp").exists());
+
+    let manifest = Manifest::load(&manifest_path).unwrap();
+    assert!(is_up_to_date(&manifest, &config));
+
+    // Changed options or content are converted again.
+    config.format = RdfFormat::NTriples;
+    assert!(!is_up_to_date(&manifest, &config));
+    config.format = RdfFormat::Turtle;
+    let results = manifest.process_files(&inputs, &config, Some(InputType::Bravo), 1, true, |_| {});
+    assert!(!matches!(results[0].outcome, Outcome::UpToDate { .. }));
+    fs::write(&input_path, format!("{}\n", campaign)).unwrap();
+    assert!(!is_up_to_date(&manifest, &config));
+}
+
+#[test]
+fn test_incremental_results_follow_inputs() {
+    let root = std::env::temp_dir().join("catplus_incremental_tests_order");
+    let _ = fs::remove_dir_all(&root);
+    fs::create_dir_all(&root).unwrap();
+    let config = get_test_config("data/tests/synth_add_action.json");
+    let inputs: Vec<(PathBuf, PathBuf)> =
+        ["synth_add_action.json", "synth_shake_action.json", "synth_filtrate_action.json"]
+            .iter()
+            .map(|name| {
+                let input_path = root.join(name);
+                fs::copy(get_data_path(&format!("data/tests/{}", name)), &input_path).unwrap();
+                (input_path, root.clone())
+            })
+            .collect();
+
+    // Only the first and last files are up to date.
+    let mut manifest = Manifest::default();
+    let converted = [inputs[0].clone(), inputs[2].clone()];
+    let results = manifest.process_files(&converted, &config, None, 2, false, |_| {});
+    manifest.record(&results, &config, None);
+
+    let mut logged = Vec::new();
+    let results = manifest.process_files(&inputs, &config, None, 2, false, |result| {
+        logged.push(result.input_path.clone())
+    });
+    let input_paths: Vec<PathBuf> = inputs.into_iter().map(|(path, _)| path).collect();
+    assert_eq!(logged, input_paths);
+    assert!(matches!(results[0].outcome, Outcome::UpToDate { .. }));
+    assert!(matches!(results[1].outcome, Outcome::Converted { .. }));
+    assert!(matches!(results[2].outcome, Outcome::UpToDate { .. }));
+}
+
+#[test]
+fn test_incremental_records_converted_content() {
+    let root = std::env::temp_dir().join("catplus_incremental_tests_changed");
+    let _ = fs::remove_dir_all(&root);
+    fs::create_dir_all(&root).unwrap();
+    let campaign = fs::read_to_string(get_data_path("data/tests/hci_campaign.json")).unwrap();
+    let input_path = root.join("campaign.json");
+    fs::write(&input_path, &campaign).unwrap();
+    let inputs = vec![(input_path.clone(), root.clone())];
+    let config = get_test_config("data/tests/hci_campaign.json");
+
+    // The file changes after it is converted, before the manifest is recorded.
+    let mut manifest = Manifest::default();
+    let results = manifest.process_files(&inputs, &config, None, 1, false, |_| {});
+    fs::write(&input_path, format!("{}\n", campaign)).unwrap();
+    manifest.record(&results, &config, None);
+
+    let results = manifest.process_files(&inputs, &config, None, 1, false, |_| {});
+    assert!(matches!(results[0].outcome, Outcome::Converted { .. }));
+}
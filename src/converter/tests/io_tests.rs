@@ -0,0 +1,106 @@
+use converter::io::{
+    collect_input_files, detect_input_type_from_content, detect_input_type_from_filename,
+    determine_input_action, mirror_output_folder, DetectionRule, InputAction, InputType,
+};
+use std::{fs, path::Path};
+
+mod common;
+use common::get_data_path;
+
+fn detect_file(path: &str) -> Option<(InputType, DetectionRule)> {
+    let content = fs::read_to_string(get_data_path(path)).unwrap();
+    detect_input_type_from_content(&content)
+}
+
+#[test]
+fn test_detect_input_type_from_content() {
+    assert_eq!(detect_file("data/tests/hci_campaign.json").unwrap().0, InputType::HCI);
+    assert_eq!(
+        detect_file("data/tests/agilent_device_system_document.json").unwrap().0,
+        InputType::Agilent
+    );
+    assert_eq!(detect_file("data/tests/synth_add_action.json").unwrap().0, InputType::Synth);
+    assert_eq!(detect_file("data/tests/bravo1_add_action.json").unwrap().0, InputType::Bravo);
+    assert!(detect_input_type_from_content(r#"{"unrelated": 1}"#).is_none());
+    assert!(detect_input_type_from_content("not json").is_none());
+}
+
+#[test]
+fn test_detect_input_type_from_filename() {
+    assert_eq!(
+        detect_input_type_from_filename("2-Agilent.json"),
+        Some((InputType::Agilent, DetectionRule::Filename("agilent")))
+    );
+    assert!(detect_input_type_from_filename("export.json").is_none());
+}
+
+#[test]
+fn test_determine_input_action_renamed_file() {
+    let dir = std::env::temp_dir().join("catplus_io_tests_renamed");
+    fs::create_dir_all(&dir).unwrap();
+    let renamed = dir.join("export_0001.json");
+    fs::copy(get_data_path("data/tests/synth_add_action.json"), &renamed).unwrap();
+
+    match determine_input_action(&renamed, None).unwrap() {
+        InputAction::Process(input_type, rule, _) => {
+            assert_eq!(input_type, InputType::Synth);
+            assert!(matches!(rule, DetectionRule::Content(_)));
+        }
+        InputAction::Skip(reason) => panic!("Unexpected skip: {}", reason),
+    }
+
+    match determine_input_action(&renamed, Some(InputType::Bravo)).unwrap() {
+        InputAction::Process(input_type, rule, _) => {
+            assert_eq!(input_type, InputType::Bravo);
+            assert_eq!(rule, DetectionRule::Explicit);
+        }
+        InputAction::Skip(reason) => panic!("Unexpected skip: {}", reason),
+    }
+}
+
+#[test]
+fn test_determine_input_action_unreadable_file() {
+    let dir = std::env::temp_dir().join("catplus_io_tests_unreadable");
+    fs::create_dir_all(&dir).unwrap();
+
+    // Without a matching filename, a file that cannot be read is skipped like unknown content.
+    let action = determine_input_action(&dir.join("missing.json"), None).unwrap();
+    assert!(matches!(action, InputAction::Skip(_)));
+    assert!(determine_input_action(&dir.join("missing_synth.json"), None).is_err());
+    assert!(determine_input_action(&dir.join("missing.json"), Some(InputType::HCI)).is_err());
+}
+
+#[test]
+fn test_determine_input_action_skips_rdf() {
+    let action = determine_input_action(Path::new("output.ttl"), Some(InputType::HCI)).unwrap();
+    assert!(matches!(action, InputAction::Skip(_)));
+}
+
+#[test]
+fn test_collect_input_files_recursive() {
+    let root = std::env::temp_dir().join("catplus_io_tests_recursive");
+    let nested = root.join("batch/2025/10/01/24");
+    fs::create_dir_all(&nested).unwrap();
+    fs::write(root.join("top.json"), "{}").unwrap();
+    fs::write(nested.join("nested.json"), "{}").unwrap();
+
+    let flat = collect_input_files(&root, false).unwrap();
+    assert_eq!(flat, vec![root.join("top.json")]);
+
+    let all = collect_input_files(&root, true).unwrap();
+    assert_eq!(all, vec![nested.join("nested.json"), root.join("top.json")]);
+}
+
+#[test]
+fn test_mirror_output_folder() {
+    let output = mirror_output_folder(
+        Path::new("input"),
+        Path::new("input/batch/2025/file.json"),
+        Path::new("output"),
+    );
+    assert_eq!(output, Path::new("output/batch/2025"));
+
+    let output =
+        mirror_output_folder(Path::new("input"), Path::new("input/file.json"), Path::new("output"));
+    assert_eq!(output, Path::new("output"));
+}
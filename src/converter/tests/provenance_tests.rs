@@ -0,0 +1,94 @@
+use catplus_common::{
+    graph::{
+        namespaces::{cat, prov, schema},
+        provenance::ProvenanceGraph,
+    },
+    models::hci::CampaignWrapper,
+};
+use converter::{
+    batch::{build_graph, merge_files},
+    convert::{json_to_graph, serialize_graph, InputTypeOverrides, RdfFormat},
+    io::InputType,
+};
+use sophia::api::{ns::rdf, prelude::*};
+use sophia_isomorphism::isomorphic_graphs;
+
+mod common;
+use common::{get_data_path, get_test_config};
+
+#[test]
+fn test_provenance_in_default_graph() {
+    let mut config = get_test_config("data/tests/hci_campaign.json");
+    config.provenance = Some(ProvenanceGraph::Default);
+    let graph_builder = build_graph(&config, InputType::HCI).unwrap();
+    let graph = &graph_builder.graph;
+
+    let [campaign, _, _] =
+        graph.triples_matching(Any, [rdf::type_], [cat::Campaign]).next().unwrap().unwrap();
+    let [_, _, activity] =
+        graph.triples_matching([campaign], [prov::wasGeneratedBy], Any).next().unwrap().unwrap();
+    assert!(graph.triples_matching([activity], [rdf::type_], [prov::Activity]).next().is_some());
+
+    let parameters: Vec<String> = graph
+        .triples_matching(Any, [schema::name], Any)
+        .filter_map(Result::ok)
+        .filter_map(|[_, _, name]| name.lexical_form().map(|name| name.to_string()))
+        .collect();
+    assert!(parameters.contains(&"input-type".to_string()));
+    assert!(parameters.contains(&"materialize".to_string()));
+    assert_eq!(graph.triples_matching(Any, [schema::sha256], Any).count(), 1);
+}
+
+#[test]
+fn test_provenance_in_named_graph() {
+    let mut config = get_test_config("data/tests/hci_campaign.json");
+    let without_provenance = json_to_graph::<CampaignWrapper>(&config).unwrap();
+
+    config.provenance = Some(ProvenanceGraph::Named);
+    config.format = RdfFormat::NQuads;
+    let graph_builder = json_to_graph::<CampaignWrapper>(&config).unwrap();
+    assert!(isomorphic_graphs(&graph_builder.graph, &without_provenance.graph).unwrap());
+    assert!(graph_builder.provenance_graph.is_some());
+
+    let nquads = graph_builder.serialize_to_nquads().unwrap();
+    let provenance_graph =
+        "<http://example.org/test/../../data/tests/hci_campaign.json#provenance>.";
+    assert!(nquads.lines().any(|line| line.ends_with(provenance_graph)));
+}
+
+#[test]
+fn test_named_provenance_format_overrides() {
+    let mut config = get_test_config("data/tests/hci_campaign.json");
+    config.provenance = Some(ProvenanceGraph::Named);
+    config.format = RdfFormat::Trig;
+    assert!(config.check_provenance_format().is_ok());
+
+    // An input type written as turtle would drop the provenance graph.
+    let overrides = InputTypeOverrides { format: Some(RdfFormat::Turtle), ..Default::default() };
+    config.input_type_overrides.insert(InputType::HCI, overrides);
+    let err = config.check_provenance_format().unwrap_err();
+    assert!(err.to_string().contains("HCI inputs are written as Turtle"));
+
+    let graph_builder = build_graph(&config, InputType::HCI).unwrap();
+    assert!(serialize_graph(&graph_builder, &config.for_input_type(InputType::HCI)).is_err());
+}
+
+#[test]
+fn test_provenance_in_named_graph_when_merging() {
+    let mut config = get_test_config("data/tests/hci_campaign.json");
+    config.provenance = Some(ProvenanceGraph::Named);
+    config.format = RdfFormat::NQuads;
+    let inputs = [
+        get_data_path("data/tests/synth_add_action.json"),
+        get_data_path("data/tests/synth_shake_action.json"),
+    ];
+    let output_path = std::path::Path::new("merged.nq");
+    let (merged, _) = merge_files(&inputs, &config, None, 1, false, output_path, |_| {}).unwrap();
+
+    let nquads = serialize_graph(&merged, &config).unwrap();
+    let provenance_graph = "<http://example.org/test/merged.nq#provenance>.";
+    let provenance_lines: Vec<&str> =
+        nquads.lines().filter(|line| line.contains("http://www.w3.org/ns/prov#")).collect();
+    assert!(!provenance_lines.is_empty());
+    assert!(provenance_lines.iter().all(|line| line.ends_with(provenance_graph)));
+}
@@ -1,4 +1,7 @@
-use catplus_common::{models::synth::SynthBatch, rdf::rdf_parser::parse_turtle_to_graph};
+use catplus_common::{
+    models::synth::SynthBatch,
+    rdf::{rdf_diff::diff_graphs, rdf_parser::parse_turtle_to_graph},
+};
 use converter::convert::json_to_rdf;
 use sophia_isomorphism::isomorphic_graphs;
 
@@ -48,7 +51,7 @@ fn test_convert_filtrate_action() {
     let result_ttl = result.as_ref().unwrap().as_str();
     let result_graph = parse_turtle_to_graph(&result_ttl).unwrap();
     let graphs_match = isomorphic_graphs(&result_graph, &expected_graph);
-    assert_eq!(graphs_match.unwrap(), true);
+    assert!(graphs_match.unwrap(), "{}", diff_graphs(&expected_graph, &result_graph).unwrap());
 }
 
 #[test]
@@ -100,7 +103,7 @@ fn test_convert_pressure_action() {
     let result_ttl = result.as_ref().unwrap().as_str();
     let result_graph = parse_turtle_to_graph(&result_ttl).unwrap();
     let graphs_match = isomorphic_graphs(&result_graph, &expected_graph);
-    assert_eq!(graphs_match.unwrap(), true);
+    assert!(graphs_match.unwrap(), "{}", diff_graphs(&expected_graph, &result_graph).unwrap());
 }
 
 #[test]
@@ -164,7 +167,7 @@ fn test_convert_set_temperature_action() {
     let result_ttl = result.as_ref().unwrap().as_str();
     let result_graph = parse_turtle_to_graph(&result_ttl).unwrap();
     let graphs_match = isomorphic_graphs(&result_graph, &expected_graph);
-    assert_eq!(graphs_match.unwrap(), true);
+    assert!(graphs_match.unwrap(), "{}", diff_graphs(&expected_graph, &result_graph).unwrap());
 }
 
 #[test]
@@ -317,7 +320,7 @@ fn test_convert_add_action() {
     let result_ttl = result.as_ref().unwrap().as_str();
     let result_graph = parse_turtle_to_graph(&result_ttl).unwrap();
     let graphs_match = isomorphic_graphs(&result_graph, &expected_graph);
-    assert_eq!(graphs_match.unwrap(), true);
+    assert!(graphs_match.unwrap(), "{}", diff_graphs(&expected_graph, &result_graph).unwrap());
 }
 
 #[test]
@@ -381,7 +384,7 @@ fn test_convert_shake_action() {
     let result_ttl = result.as_ref().unwrap().as_str();
     let result_graph = parse_turtle_to_graph(&result_ttl).unwrap();
     let graphs_match = isomorphic_graphs(&result_graph, &expected_graph);
-    assert_eq!(graphs_match.unwrap(), true);
+    assert!(graphs_match.unwrap(), "{}", diff_graphs(&expected_graph, &result_graph).unwrap());
 }
 
 #[test]
@@ -433,5 +436,5 @@ fn test_convert_set_vacuum_action() {
     let result_ttl = result.as_ref().unwrap().as_str();
     let result_graph = parse_turtle_to_graph(&result_ttl).unwrap();
     let graphs_match = isomorphic_graphs(&result_graph, &expected_graph);
-    assert_eq!(graphs_match.unwrap(), true);
+    assert!(graphs_match.unwrap(), "{}", diff_graphs(&expected_graph, &result_graph).unwrap());
 }
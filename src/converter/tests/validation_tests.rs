@@ -0,0 +1,86 @@
+use converter::{
+    batch::{process_file, Outcome},
+    report::{ConversionReport, Status},
+    validate::{OnInvalid, ValidationConfig, QUARANTINE_FOLDER},
+};
+use std::{fs, path::Path};
+
+mod common;
+use common::get_test_config;
+
+const SHAPES: &str = r#"
+    @prefix sh: <http://www.w3.org/ns/shacl#> .
+    @prefix cat: <http://example.org/catplus/ontology/> .
+    @prefix schema: <https://schema.org/> .
+    @prefix ex: <http://example.org/shapes/> .
+
+    ex:BatchShape a sh:NodeShape ;
+        sh:targetClass cat:Batch ;
+        sh:property [ sh:path cat:reactionName ; sh:minCount 1 ] ;
+        sh:property [ sh:path schema:name ; sh:pattern "^%s" ; sh:message "Unexpected batch name" ] .
+"#;
+
+/// Writes shapes requiring batch names starting with `name_pattern` in `folder`.
+fn write_shapes(folder: &Path, name_pattern: &str) -> std::path::PathBuf {
+    let shapes_path = folder.join(format!("shapes_{}.ttl", name_pattern));
+    fs::write(&shapes_path, SHAPES.replace("%s", name_pattern)).unwrap();
+    shapes_path
+}
+
+fn validation(shapes_path: &Path, on_invalid: OnInvalid) -> Option<ValidationConfig> {
+    Some(ValidationConfig::new(Some(shapes_path), None, on_invalid).unwrap())
+}
+
+#[test]
+fn test_validate_during_conversion() {
+    let root = std::env::temp_dir().join("catplus_validation_tests");
+    let _ = fs::remove_dir_all(&root);
+    fs::create_dir_all(&root).unwrap();
+    let valid_shapes = write_shapes(&root, "2024");
+    let invalid_shapes = write_shapes(&root, "2025");
+    let mut config = get_test_config("data/tests/hci_campaign.json");
+
+    config.validation = validation(&valid_shapes, OnInvalid::Fail);
+    let result = process_file(&config, &root.join("valid"), None);
+    assert!(matches!(result.outcome, Outcome::Converted { .. }));
+
+    config.validation = validation(&invalid_shapes, OnInvalid::Fail);
+    let result = process_file(&config, &root.join("fail"), None);
+    match &result.outcome {
+        Outcome::Failed(err) => assert!(format!("{:#}", err).contains("Unexpected batch name")),
+        outcome => panic!("Unexpected outcome: {:?}", outcome),
+    }
+    assert!(!root.join("fail").join("hci_campaign.ttl").exists());
+
+    config.validation = validation(&invalid_shapes, OnInvalid::Warn);
+    let result = process_file(&config, &root.join("warn"), None);
+    match &result.outcome {
+        Outcome::Invalid { output_path, report_path: None, reason, .. } => {
+            assert_eq!(*output_path, root.join("warn").join("hci_campaign.ttl"));
+            assert!(output_path.exists());
+            assert!(reason.contains("1 violation"));
+        }
+        outcome => panic!("Unexpected outcome: {:?}", outcome),
+    }
+
+    config.validation = validation(&invalid_shapes, OnInvalid::Quarantine);
+    let result = process_file(&config, &root.join("quarantine"), None);
+    let quarantine_folder = root.join("quarantine").join(QUARANTINE_FOLDER);
+    match &result.outcome {
+        Outcome::Invalid { output_path, report_path: Some(report_path), .. } => {
+            assert_eq!(*output_path, quarantine_folder.join("hci_campaign.ttl"));
+            assert_eq!(*report_path, quarantine_folder.join("hci_campaign.validation.ttl"));
+            assert!(output_path.exists());
+            assert!(fs::read_to_string(report_path).unwrap().contains("Unexpected batch name"));
+        }
+        outcome => panic!("Unexpected outcome: {:?}", outcome),
+    }
+    let report = ConversionReport::from_results(&[result]);
+    assert_eq!(report.invalid, 1);
+    assert_eq!(report.files[0].status, Status::Invalid);
+}
+
+#[test]
+fn test_validation_requires_shapes() {
+    assert!(ValidationConfig::new(None, None, OnInvalid::Fail).is_err());
+}
@@ -0,0 +1,44 @@
+use converter::{batch::Outcome, watch::Watcher};
+use std::{fs, time::Duration};
+
+mod common;
+use common::{get_data_path, get_test_config};
+
+#[test]
+fn test_watch_converts_new_and_modified_files() {
+    let root = std::env::temp_dir().join("catplus_watch_tests");
+    let _ = fs::remove_dir_all(&root);
+    let input_folder = root.join("drop");
+    let output_folder = root.join("rdf");
+    fs::create_dir_all(&input_folder).unwrap();
+    let state_path = root.join("state.json");
+    let config = get_test_config("data/tests/hci_campaign.json");
+    let new_watcher = |settle_delay| {
+        Watcher::new(&input_folder, &output_folder, &config, None, false, settle_delay, &state_path)
+            .unwrap()
+    };
+
+    let campaign = fs::read_to_string(get_data_path("data/tests/hci_campaign.json")).unwrap();
+    fs::write(input_folder.join("campaign.json"), &campaign).unwrap();
+    fs::write(input_folder.join("notes.txt"), "not converted").unwrap();
+
+    // Files modified within the settle delay are left alone.
+    let mut watcher = new_watcher(Duration::from_secs(3600));
+    assert!(watcher.poll(|_| {}).unwrap().is_empty());
+
+    let mut watcher = new_watcher(Duration::ZERO);
+    let results = watcher.poll(|_| {}).unwrap();
+    assert_eq!(results.len(), 1);
+    assert!(matches!(results[0].outcome, Outcome::Converted { .. }));
+    assert!(output_folder.join("campaign.ttl").exists());
+    assert!(watcher.poll(|_| {}).unwrap().is_empty());
+
+    // A restarted watcher resumes from the state file.
+    let mut watcher = new_watcher(Duration::ZERO);
+    assert!(watcher.poll(|_| {}).unwrap().is_empty());
+
+    fs::write(input_folder.join("campaign.json"), format!("{}\n", campaign)).unwrap();
+    let results = watcher.poll(|_| {}).unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(watcher.state.files.len(), 1);
+}
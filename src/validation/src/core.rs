@@ -1,35 +1,187 @@
 /// Interface for validating an RDF graph.
+use catplus_common::graph::namespaces::sh;
 use sophia::inmem::graph::LightGraph;
-use sophia_api::prelude::*;
-use std::error::Error;
+use sophia_api::{ns::NsTerm, prelude::*, term::SimpleTerm};
+use std::{cmp::Reverse, collections::HashMap, error::Error, fmt};
+
+/// Severity of a validation result, ordered from the least to the most severe.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Severity {
+    Info,
+    Warning,
+    Violation,
+}
+
+impl Severity {
+    pub const ALL: [Severity; 3] = [Severity::Violation, Severity::Warning, Severity::Info];
+
+    /// Returns the severity of an `sh:resultSeverity` IRI.
+    /// Custom severities are not supported and treated as violations.
+    pub fn from_term<T: Term>(term: T) -> Self {
+        if Term::eq(&term, sh::Info) {
+            Severity::Info
+        } else if Term::eq(&term, sh::Warning) {
+            Severity::Warning
+        } else {
+            Severity::Violation
+        }
+    }
+
+    pub fn iri(&self) -> NsTerm<'static> {
+        match self {
+            Severity::Info => sh::Info,
+            Severity::Warning => sh::Warning,
+            Severity::Violation => sh::Violation,
+        }
+    }
+}
+
+/// A `sh:ValidationResult` of a validation report.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ValidationResult {
+    pub focus_node: SimpleTerm<'static>,
+    pub result_path: Option<SimpleTerm<'static>>,
+    pub value: Option<SimpleTerm<'static>>,
+    pub source_shape: Option<SimpleTerm<'static>>,
+    pub source_constraint_component: Option<SimpleTerm<'static>>,
+    pub severity: Severity,
+    /// First `sh:resultMessage` of the result, if any.
+    pub message: Option<String>,
+}
+
+impl ValidationResult {
+    /// Parses the validation result `node` of the report `graph`.
+    fn from_graph(graph: &LightGraph, node: &SimpleTerm) -> Option<Self> {
+        let object = |predicate: NsTerm| -> Option<SimpleTerm<'static>> {
+            graph
+                .triples_matching([node], [predicate], Any)
+                .find_map(|t| t.ok().map(|t| t[2].clone().into_term()))
+        };
+        Some(ValidationResult {
+            // A result without focus node is malformed, and skipped.
+            focus_node: object(sh::focusNode)?,
+            result_path: object(sh::resultPath),
+            value: object(sh::value),
+            source_shape: object(sh::sourceShape),
+            source_constraint_component: object(sh::sourceConstraintComponent),
+            severity: object(sh::resultSeverity).map_or(Severity::Violation, Severity::from_term),
+            message: object(sh::resultMessage)
+                .and_then(|message| message.lexical_form().map(|m| m.to_string())),
+        })
+    }
+}
 
 /// A SHACL validation report for an RDF graph.
 #[derive(Clone, Debug)]
 pub struct ValidationReport {
     pub conforms: bool,
     pub graph: LightGraph,
-    //TODO: Summary of severity counts.
-    //pub summary: HashMap<Severity, u32>,
+    /// Results of the report, the most severe first.
+    pub results: Vec<ValidationResult>,
+    /// Number of results per severity.
+    pub summary: HashMap<Severity, u32>,
 }
 
 impl ValidationReport {
     pub fn new(conforms: bool, graph: LightGraph) -> Self {
-        ValidationReport { conforms, graph }
+        let results = parse_results(&graph);
+        let mut summary = HashMap::new();
+        for result in &results {
+            *summary.entry(result.severity).or_insert(0) += 1;
+        }
+        ValidationReport { conforms, graph, results, summary }
     }
 
-    pub fn from_graph(graph: LightGraph) -> Self {
+    /// Parses a report graph, failing if its `sh:conforms` is not a boolean.
+    pub fn from_graph(graph: LightGraph) -> Result<Self, Box<dyn Error + Send + Sync>> {
         // NOTE: Only looks at value of the first sh:conforms triple
         // not found -> not conform
-        let conforms = graph
-            .triples_matching(Any, ["http://www.w3.org/ns/shacl#conforms".as_simple()], Any)
-            .map(|t| t.map_or(false, |t| t[2].lexical_form().unwrap() == "true"))
-            .next()
-            .unwrap_or(false);
+        let conforms = match graph.triples_matching(Any, [sh::conforms], Any).next() {
+            Some(triple) => {
+                let value = triple?[2];
+                match value.lexical_form().as_deref() {
+                    Some("true") | Some("1") => true,
+                    Some("false") | Some("0") => false,
+                    _ => return Err(format!("Invalid sh:conforms value {:?}", value).into()),
+                }
+            }
+            None => false,
+        };
+
+        Ok(ValidationReport::new(conforms, graph))
+    }
+
+    /// Number of results with `severity`.
+    pub fn count(&self, severity: Severity) -> u32 {
+        self.summary.get(&severity).copied().unwrap_or(0)
+    }
+
+    /// Most severe severity of the results, `None` if there are none.
+    pub fn max_severity(&self) -> Option<Severity> {
+        self.results.iter().map(|result| result.severity).max()
+    }
+
+    pub fn with_severity(&self, severity: Severity) -> impl Iterator<Item = &ValidationResult> {
+        self.results.iter().filter(move |result| result.severity == severity)
+    }
+
+    pub fn for_focus_node<T: Term>(
+        &self,
+        focus_node: T,
+    ) -> impl Iterator<Item = &ValidationResult> {
+        self.results
+            .iter()
+            .filter(move |result| Term::eq(&result.focus_node, focus_node.borrow_term()))
+    }
 
-        ValidationReport { conforms, graph }
+    pub fn for_shape<T: Term>(&self, shape: T) -> impl Iterator<Item = &ValidationResult> {
+        self.results.iter().filter(move |result| {
+            result.source_shape.as_ref().is_some_and(|s| Term::eq(s, shape.borrow_term()))
+        })
+    }
+
+    pub fn for_component<T: Term>(&self, component: T) -> impl Iterator<Item = &ValidationResult> {
+        self.results.iter().filter(move |result| {
+            result
+                .source_constraint_component
+                .as_ref()
+                .is_some_and(|c| Term::eq(c, component.borrow_term()))
+        })
+    }
+}
+
+/// Parses the `sh:result` nodes of a report graph, sorted by decreasing severity then focus node.
+fn parse_results(graph: &LightGraph) -> Vec<ValidationResult> {
+    let mut results: Vec<ValidationResult> = graph
+        .triples_matching(Any, [sh::result], Any)
+        .filter_map(|t| t.ok())
+        .filter_map(|t| ValidationResult::from_graph(graph, t[2]))
+        .collect();
+    results.sort_by(|a, b| {
+        (Reverse(a.severity), &a.focus_node, &a.result_path, &a.source_constraint_component).cmp(&(
+            Reverse(b.severity),
+            &b.focus_node,
+            &b.result_path,
+            &b.source_constraint_component,
+        ))
+    });
+    results
+}
+
+/// Error of an engine for shapes that it cannot evaluate: malformed shapes, or shapes
+/// using features the engine does not support. Unlike the other engine errors, it is
+/// caused by the input rather than by the engine.
+#[derive(Debug)]
+pub struct InvalidShapes(pub String);
+
+impl fmt::Display for InvalidShapes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
     }
 }
 
+impl Error for InvalidShapes {}
+
 /// Interface for a SHACL validation engine.
 pub trait ShaclEngine {
     /// Check if the engine can be used.
@@ -41,8 +193,100 @@ pub trait ShaclEngine {
         &self,
         data: &LightGraph,
         shapes: Option<&LightGraph>,
-    ) -> Result<ValidationReport, Box<dyn Error>>;
+    ) -> Result<ValidationReport, Box<dyn Error + Send + Sync>>;
 
     // TODO: SHACL inference
-    // fn infer(&self, data: &LightGraph, rules: Option<&LightGraph>) -> Result<LightGraph, Box<dyn Error>>;
+    // fn infer(&self, data: &LightGraph, rules: Option<&LightGraph>) -> Result<LightGraph, Box<dyn Error + Send + Sync>>;
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use catplus_common::rdf::rdf_parser::parse_turtle_to_graph;
+    use sophia_api::term::IriRef;
+
+    fn ex(name: &str) -> SimpleTerm<'static> {
+        IriRef::new_unchecked(format!("http://example.org/{}", name)).into_term()
+    }
+
+    const REPORT: &str = r#"
+        @prefix sh: <http://www.w3.org/ns/shacl#> .
+        @prefix ex: <http://example.org/> .
+
+        [] a sh:ValidationReport ;
+            sh:conforms false ;
+            sh:result [
+                a sh:ValidationResult ;
+                sh:focusNode ex:alice ;
+                sh:resultPath ex:name ;
+                sh:resultSeverity sh:Warning ;
+                sh:sourceConstraintComponent sh:PatternConstraintComponent ;
+                sh:sourceShape ex:NameShape ;
+                sh:value "alice" ;
+                sh:resultMessage "Names are capitalized" ;
+            ] , [
+                a sh:ValidationResult ;
+                sh:focusNode ex:bob ;
+                sh:resultPath ex:name ;
+                sh:resultSeverity sh:Violation ;
+                sh:sourceConstraintComponent sh:MinCountConstraintComponent ;
+                sh:sourceShape ex:NameShape ;
+            ] , [
+                a sh:ValidationResult ;
+                sh:focusNode ex:alice ;
+                sh:resultSeverity sh:Info ;
+                sh:sourceConstraintComponent sh:ClassConstraintComponent ;
+            ] .
+    "#;
+
+    #[test]
+    fn test_report_results() {
+        let report = ValidationReport::from_graph(parse_turtle_to_graph(REPORT).unwrap()).unwrap();
+        assert!(!report.conforms);
+        assert_eq!(report.results.len(), 3);
+
+        let result = &report.results[0];
+        assert_eq!(result.severity, Severity::Violation);
+        assert!(Term::eq(&result.focus_node, ex("bob")));
+        assert!(result.value.is_none() && result.message.is_none());
+        let result = &report.results[1];
+        assert_eq!(result.severity, Severity::Warning);
+        assert_eq!(result.value.as_ref().unwrap().lexical_form().unwrap(), "alice");
+        assert_eq!(result.message.as_deref(), Some("Names are capitalized"));
+
+        for severity in Severity::ALL {
+            assert_eq!(report.count(severity), 1);
+        }
+        assert_eq!(report.max_severity(), Some(Severity::Violation));
+    }
+
+    #[test]
+    fn test_report_conforms_value() {
+        let report = |conforms: &str| {
+            let turtle = format!(
+                "@prefix sh: <http://www.w3.org/ns/shacl#> . [] sh:conforms {} .",
+                conforms
+            );
+            ValidationReport::from_graph(parse_turtle_to_graph(&turtle).unwrap())
+        };
+        assert!(report("true").unwrap().conforms);
+        assert!(!report("false").unwrap().conforms);
+        assert!(report("<http://example.org/yes>").is_err());
+        assert!(report("\"maybe\"").is_err());
+    }
+
+    #[test]
+    fn test_report_filters() {
+        let report = ValidationReport::from_graph(parse_turtle_to_graph(REPORT).unwrap()).unwrap();
+        let alice = ex("alice");
+        assert_eq!(report.for_focus_node(alice).count(), 2);
+        assert_eq!(report.for_shape(ex("NameShape")).count(), 2);
+        assert_eq!(report.for_component(sh::PatternConstraintComponent).count(), 1);
+        assert_eq!(report.with_severity(Severity::Info).count(), 1);
+
+        let conforming = ValidationReport::new(true, LightGraph::new());
+        assert!(conforming.results.is_empty());
+        assert_eq!(conforming.count(Severity::Violation), 0);
+        assert_eq!(conforming.max_severity(), None);
+    }
 }
@@ -0,0 +1,1039 @@
+/// A native implementation of a SHACL validation engine.
+/// Evaluates the SHACL Core constraints on the data graph, without any external service.
+use regex::{Regex, RegexBuilder};
+use sophia::{
+    api::ns::{rdf, rdfs, xsd},
+    inmem::graph::LightGraph,
+};
+use sophia_api::{
+    ns::NsTerm,
+    prelude::*,
+    term::{bnode_id::BnodeId, matcher::TermMatcher, SimpleTerm, TermKind},
+};
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+    error::Error,
+};
+
+use crate::core::{InvalidShapes, ShaclEngine, ValidationReport};
+use catplus_common::graph::namespaces::sh;
+
+type Node = SimpleTerm<'static>;
+/// Shapes and their focus nodes.
+type Targets = Vec<(Node, Vec<Node>)>;
+/// List predicate and component of a logical constraint, whether a number of conforming
+/// shapes out of the listed shapes satisfies it, and how many shapes it requires.
+type LogicalConstraint = (NsTerm<'static>, NsTerm<'static>, fn(usize, usize) -> bool, &'static str);
+
+/// SHACL engine evaluating the shapes in process.
+///
+/// Supports the targets, `sh:property` and `sh:node` shapes, property paths
+/// (sequence, inverse, alternative and repetitions) and the value type, cardinality,
+/// value range, string, `sh:in`, `sh:hasValue` and logical constraint components of
+/// SHACL Core. Shapes using the other constraint components are rejected with an error,
+/// rather than ignored.
+#[derive(Default)]
+pub struct NativeShaclEngine {
+    default_shapes: Option<LightGraph>,
+}
+
+impl NativeShaclEngine {
+    pub fn new() -> Self {
+        NativeShaclEngine { default_shapes: None }
+    }
+
+    /// Uses `shapes` when [ShaclEngine::validate] is called without shapes.
+    pub fn with_default_shapes(shapes: LightGraph) -> Self {
+        NativeShaclEngine { default_shapes: Some(shapes) }
+    }
+}
+
+impl ShaclEngine for NativeShaclEngine {
+    fn is_available(&self) -> bool {
+        true
+    }
+
+    fn validate(
+        &self,
+        data: &LightGraph,
+        shapes: Option<&LightGraph>,
+    ) -> Result<ValidationReport, Box<dyn Error + Send + Sync>> {
+        let shapes = shapes
+            .or(self.default_shapes.as_ref())
+            .ok_or_else(|| invalid("The native SHACL engine requires shapes, none were given"))?;
+
+        let validator = Validator::new(data, shapes);
+        validator.check_supported()?;
+        let mut results = Vec::new();
+        for (shape, focus_nodes) in validator.targets()? {
+            for focus_node in focus_nodes {
+                validator.validate_shape(&shape, &focus_node, &mut results, &mut Vec::new())?;
+            }
+        }
+
+        ValidationReport::from_graph(report_graph(&results, shapes)?)
+    }
+}
+
+/// A violation of a constraint, as a `sh:ValidationResult`.
+struct ShapeResult {
+    focus_node: Node,
+    path: Option<Node>,
+    value: Option<Node>,
+    shape: Node,
+    component: Node,
+    severity: Node,
+    message: String,
+}
+
+/// Evaluates the shapes of a shapes graph on a data graph.
+struct Validator<'a> {
+    data: &'a LightGraph,
+    shapes: &'a LightGraph,
+    patterns: std::cell::RefCell<HashMap<(String, String), Regex>>,
+}
+
+impl<'a> Validator<'a> {
+    fn new(data: &'a LightGraph, shapes: &'a LightGraph) -> Self {
+        Validator { data, shapes, patterns: Default::default() }
+    }
+
+    /// Fails if a shape uses a constraint component that is not implemented, since
+    /// ignoring it would let non-conforming data pass.
+    fn check_supported(&self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        for predicate in [
+            sh::closed,
+            sh::equals,
+            sh::disjoint,
+            sh::lessThan,
+            sh::lessThanOrEquals,
+            sh::languageIn,
+            sh::uniqueLang,
+            sh::qualifiedValueShape,
+            sh::sparql,
+        ] {
+            for shape in subjects(self.shapes, predicate, Any)? {
+                if Term::eq(&predicate, sh::closed) && !self.is_true(&shape, sh::closed)? {
+                    continue;
+                }
+                return Err(invalid(format!(
+                    "Unsupported constraint {} on shape {}, use a SHACL API server instead",
+                    display(&predicate.into_term()),
+                    display(&shape)
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the shapes with targets and their focus nodes in the data graph.
+    fn targets(&self) -> Result<Targets, Box<dyn Error + Send + Sync>> {
+        let mut shapes: Vec<Node> = Vec::new();
+        for predicate in
+            [sh::targetClass, sh::targetNode, sh::targetSubjectsOf, sh::targetObjectsOf]
+        {
+            shapes.extend(subjects(self.shapes, predicate, Any)?);
+        }
+        // Shapes that are also classes target their instances.
+        for shape_type in [sh::NodeShape, sh::PropertyShape] {
+            for shape in subjects(self.shapes, rdf::type_, [shape_type])? {
+                if has_triple(self.shapes, &shape, rdf::type_, [rdfs::Class])? {
+                    shapes.push(shape);
+                }
+            }
+        }
+        dedup(&mut shapes);
+
+        let mut targets = Vec::new();
+        for shape in shapes {
+            let mut focus_nodes = objects(self.shapes, &shape, sh::targetNode)?;
+            let mut classes = objects(self.shapes, &shape, sh::targetClass)?;
+            if has_triple(self.shapes, &shape, rdf::type_, [rdfs::Class])? {
+                classes.push(shape.clone());
+            }
+            for class in classes {
+                focus_nodes.extend(self.instances(&class)?);
+            }
+            for predicate in objects(self.shapes, &shape, sh::targetSubjectsOf)? {
+                focus_nodes.extend(subjects(self.data, &predicate, Any)?);
+            }
+            for predicate in objects(self.shapes, &shape, sh::targetObjectsOf)? {
+                for triple in self.data.triples_matching(Any, [&predicate], Any) {
+                    focus_nodes.push(triple?[2].clone().into_term());
+                }
+            }
+            dedup(&mut focus_nodes);
+            targets.push((shape, focus_nodes));
+        }
+        Ok(targets)
+    }
+
+    /// Validates `focus_node` against `shape`, adding the violations to `results`.
+    ///
+    /// `in_progress` holds the shapes and focus nodes being validated, so that recursive
+    /// shapes terminate: a node is assumed to conform to a shape it is already checked against.
+    fn validate_shape(
+        &self,
+        shape: &Node,
+        focus_node: &Node,
+        results: &mut Vec<ShapeResult>,
+        in_progress: &mut Vec<(Node, Node)>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        if self.is_true(shape, sh::deactivated)?
+            || in_progress.contains(&(shape.clone(), focus_node.clone()))
+        {
+            return Ok(());
+        }
+        in_progress.push((shape.clone(), focus_node.clone()));
+
+        let path = object(self.shapes, shape, sh::path)?;
+        let value_nodes = match &path {
+            Some(path) => self.evaluate_path(path, focus_node, false)?,
+            None => vec![focus_node.clone()],
+        };
+        let severity =
+            object(self.shapes, shape, sh::severity)?.unwrap_or_else(|| sh::Violation.into_term());
+        let shape_message = object(self.shapes, shape, sh::message)?
+            .and_then(|message| message.lexical_form().map(|m| m.to_string()));
+        let mut report = |value: Option<&Node>, component: NsTerm<'static>, message: String| {
+            results.push(ShapeResult {
+                focus_node: focus_node.clone(),
+                path: path.clone(),
+                value: value.cloned(),
+                shape: shape.clone(),
+                component: component.into_term(),
+                severity: severity.clone(),
+                message: shape_message.clone().unwrap_or(message),
+            });
+        };
+
+        // Cardinality
+        if let Some(min_count) = self.integer(shape, sh::minCount)? {
+            if (value_nodes.len() as i64) < min_count {
+                report(
+                    None,
+                    sh::MinCountConstraintComponent,
+                    format!("Less than {} values on {}", min_count, display(focus_node)),
+                );
+            }
+        }
+        if let Some(max_count) = self.integer(shape, sh::maxCount)? {
+            if (value_nodes.len() as i64) > max_count {
+                report(
+                    None,
+                    sh::MaxCountConstraintComponent,
+                    format!("More than {} values on {}", max_count, display(focus_node)),
+                );
+            }
+        }
+
+        // Value type
+        for datatype in objects(self.shapes, shape, sh::datatype)? {
+            for value in &value_nodes {
+                if !has_datatype(value, &datatype) {
+                    report(
+                        Some(value),
+                        sh::DatatypeConstraintComponent,
+                        format!(
+                            "Value {} is not a valid literal of datatype {}",
+                            display(value),
+                            display(&datatype)
+                        ),
+                    );
+                }
+            }
+        }
+        for class in objects(self.shapes, shape, sh::class)? {
+            for value in &value_nodes {
+                if !self.is_instance(value, &class)? {
+                    report(
+                        Some(value),
+                        sh::ClassConstraintComponent,
+                        format!(
+                            "Value {} is not an instance of {}",
+                            display(value),
+                            display(&class)
+                        ),
+                    );
+                }
+            }
+        }
+        for node_kind in objects(self.shapes, shape, sh::nodeKind)? {
+            for value in &value_nodes {
+                if !has_node_kind(value, &node_kind) {
+                    report(
+                        Some(value),
+                        sh::NodeKindConstraintComponent,
+                        format!("Value {} is not of kind {}", display(value), display(&node_kind)),
+                    );
+                }
+            }
+        }
+
+        // Value range
+        for (bound_predicate, component, accepted, relation) in [
+            (
+                sh::minInclusive,
+                sh::MinInclusiveConstraintComponent,
+                &[Ordering::Greater, Ordering::Equal][..],
+                "greater than or equal to",
+            ),
+            (
+                sh::minExclusive,
+                sh::MinExclusiveConstraintComponent,
+                &[Ordering::Greater][..],
+                "greater than",
+            ),
+            (
+                sh::maxInclusive,
+                sh::MaxInclusiveConstraintComponent,
+                &[Ordering::Less, Ordering::Equal][..],
+                "less than or equal to",
+            ),
+            (
+                sh::maxExclusive,
+                sh::MaxExclusiveConstraintComponent,
+                &[Ordering::Less][..],
+                "less than",
+            ),
+        ] {
+            for bound in objects(self.shapes, shape, bound_predicate)? {
+                for value in &value_nodes {
+                    if !compare_literals(value, &bound).is_some_and(|o| accepted.contains(&o)) {
+                        report(
+                            Some(value),
+                            component,
+                            format!(
+                                "Value {} is not {} {}",
+                                display(value),
+                                relation,
+                                display(&bound)
+                            ),
+                        );
+                    }
+                }
+            }
+        }
+
+        // String based
+        if let Some(min_length) = self.integer(shape, sh::minLength)? {
+            for value in &value_nodes {
+                if string_length(value).is_none_or(|length| length < min_length) {
+                    report(
+                        Some(value),
+                        sh::MinLengthConstraintComponent,
+                        format!(
+                            "Value {} is shorter than {} characters",
+                            display(value),
+                            min_length
+                        ),
+                    );
+                }
+            }
+        }
+        if let Some(max_length) = self.integer(shape, sh::maxLength)? {
+            for value in &value_nodes {
+                if string_length(value).is_none_or(|length| length > max_length) {
+                    report(
+                        Some(value),
+                        sh::MaxLengthConstraintComponent,
+                        format!(
+                            "Value {} is longer than {} characters",
+                            display(value),
+                            max_length
+                        ),
+                    );
+                }
+            }
+        }
+        for pattern in objects(self.shapes, shape, sh::pattern)? {
+            let pattern = pattern
+                .lexical_form()
+                .ok_or_else(|| invalid("sh:pattern must be a literal"))?
+                .to_string();
+            let flags = object(self.shapes, shape, sh::flags)?
+                .and_then(|flags| flags.lexical_form().map(|f| f.to_string()))
+                .unwrap_or_default();
+            for value in &value_nodes {
+                if !self.matches_pattern(value, &pattern, &flags)? {
+                    report(
+                        Some(value),
+                        sh::PatternConstraintComponent,
+                        format!("Value {} does not match pattern \"{}\"", display(value), pattern),
+                    );
+                }
+            }
+        }
+
+        // Other constraints
+        for list in objects(self.shapes, shape, sh::in_)? {
+            let allowed = rdf_list(self.shapes, &list)?;
+            for value in &value_nodes {
+                if !allowed.contains(value) {
+                    report(
+                        Some(value),
+                        sh::InConstraintComponent,
+                        format!("Value {} is not in the allowed values", display(value)),
+                    );
+                }
+            }
+        }
+
+        for expected in objects(self.shapes, shape, sh::hasValue)? {
+            if !value_nodes.contains(&expected) {
+                report(
+                    None,
+                    sh::HasValueConstraintComponent,
+                    format!("Missing value {} on {}", display(&expected), display(focus_node)),
+                );
+            }
+        }
+
+        // Logical constraints
+        for not_shape in objects(self.shapes, shape, sh::not)? {
+            for value in &value_nodes {
+                if self.conforms(&not_shape, value, in_progress)? {
+                    report(
+                        Some(value),
+                        sh::NotConstraintComponent,
+                        format!(
+                            "Value {} conforms to shape {}",
+                            display(value),
+                            display(&not_shape)
+                        ),
+                    );
+                }
+            }
+        }
+        let logical_constraints: [LogicalConstraint; 3] = [
+            (sh::and, sh::AndConstraintComponent, |conforming, count| conforming == count, "all"),
+            (sh::or, sh::OrConstraintComponent, |conforming, _| conforming > 0, "any"),
+            (sh::xone, sh::XoneConstraintComponent, |conforming, _| conforming == 1, "exactly one"),
+        ];
+        for (list_predicate, component, accepted, relation) in logical_constraints {
+            for list in objects(self.shapes, shape, list_predicate)? {
+                let member_shapes = rdf_list(self.shapes, &list)?;
+                for value in &value_nodes {
+                    let mut conforming = 0;
+                    for member_shape in &member_shapes {
+                        if self.conforms(member_shape, value, in_progress)? {
+                            conforming += 1;
+                        }
+                    }
+                    if !accepted(conforming, member_shapes.len()) {
+                        report(
+                            Some(value),
+                            component,
+                            format!(
+                                "Value {} does not conform to {} of the {} shapes",
+                                display(value),
+                                relation,
+                                member_shapes.len()
+                            ),
+                        );
+                    }
+                }
+            }
+        }
+
+        // Shape-based constraints
+        for node_shape in objects(self.shapes, shape, sh::node)? {
+            for value in &value_nodes {
+                if !self.conforms(&node_shape, value, in_progress)? {
+                    report(
+                        Some(value),
+                        sh::NodeConstraintComponent,
+                        format!(
+                            "Value {} does not conform to shape {}",
+                            display(value),
+                            display(&node_shape)
+                        ),
+                    );
+                }
+            }
+        }
+        for property_shape in objects(self.shapes, shape, sh::property)? {
+            for value in &value_nodes {
+                self.validate_shape(&property_shape, value, results, in_progress)?;
+            }
+        }
+
+        in_progress.pop();
+        Ok(())
+    }
+
+    /// Whether `node` conforms to `shape`, see [Validator::validate_shape].
+    fn conforms(
+        &self,
+        shape: &Node,
+        node: &Node,
+        in_progress: &mut Vec<(Node, Node)>,
+    ) -> Result<bool, Box<dyn Error + Send + Sync>> {
+        let mut results = Vec::new();
+        self.validate_shape(shape, node, &mut results, in_progress)?;
+        Ok(results.is_empty())
+    }
+
+    /// Returns the nodes reached from `node` through `path`, or the nodes reaching `node`
+    /// if `inverse` is set.
+    fn evaluate_path(
+        &self,
+        path: &Node,
+        node: &Node,
+        inverse: bool,
+    ) -> Result<Vec<Node>, Box<dyn Error + Send + Sync>> {
+        let mut nodes = Vec::new();
+        if path.is_iri() {
+            nodes = if inverse {
+                subjects(self.data, path, [node])?
+            } else {
+                objects(self.data, node, path)?
+            };
+        } else if has_triple(self.shapes, path, rdf::first, Any)? {
+            // Sequence path, evaluated backwards for the inverse.
+            let mut steps = rdf_list(self.shapes, path)?;
+            if inverse {
+                steps.reverse();
+            }
+            nodes.push(node.clone());
+            for step in steps {
+                let mut next = Vec::new();
+                for current in &nodes {
+                    next.extend(self.evaluate_path(&step, current, inverse)?);
+                }
+                dedup(&mut next);
+                nodes = next;
+            }
+        } else if let Some(inverse_path) = object(self.shapes, path, sh::inversePath)? {
+            nodes = self.evaluate_path(&inverse_path, node, !inverse)?;
+        } else if let Some(alternatives) = object(self.shapes, path, sh::alternativePath)? {
+            for alternative in rdf_list(self.shapes, &alternatives)? {
+                nodes.extend(self.evaluate_path(&alternative, node, inverse)?);
+            }
+        } else if let Some(step) = object(self.shapes, path, sh::zeroOrOnePath)? {
+            nodes.push(node.clone());
+            nodes.extend(self.evaluate_path(&step, node, inverse)?);
+        } else if let Some(step) = object(self.shapes, path, sh::zeroOrMorePath)? {
+            nodes.push(node.clone());
+            nodes.extend(self.closure(&step, node, inverse)?);
+        } else if let Some(step) = object(self.shapes, path, sh::oneOrMorePath)? {
+            nodes = self.closure(&step, node, inverse)?;
+        } else {
+            return Err(invalid(format!("Unsupported property path {:?}", path)));
+        }
+        dedup(&mut nodes);
+        Ok(nodes)
+    }
+
+    /// Returns the nodes reached from `node` through one or more `step` paths.
+    fn closure(
+        &self,
+        step: &Node,
+        node: &Node,
+        inverse: bool,
+    ) -> Result<Vec<Node>, Box<dyn Error + Send + Sync>> {
+        let mut reached = Vec::new();
+        let mut seen = HashSet::new();
+        let mut pending = vec![node.clone()];
+        while let Some(current) = pending.pop() {
+            for next in self.evaluate_path(step, &current, inverse)? {
+                if seen.insert(next.clone()) {
+                    reached.push(next.clone());
+                    pending.push(next);
+                }
+            }
+        }
+        Ok(reached)
+    }
+
+    /// Returns the instances of `class` or of its subclasses in the data graph.
+    fn instances(&self, class: &Node) -> Result<Vec<Node>, Box<dyn Error + Send + Sync>> {
+        let mut classes = vec![class.clone()];
+        classes.extend(self.closure(&rdfs::subClassOf.into_term(), class, true)?);
+        let mut instances = Vec::new();
+        for class in classes {
+            instances.extend(subjects(self.data, rdf::type_, [&class])?);
+        }
+        Ok(instances)
+    }
+
+    /// Whether `node` has type `class` or one of its subclasses in the data graph.
+    fn is_instance(&self, node: &Node, class: &Node) -> Result<bool, Box<dyn Error + Send + Sync>> {
+        for node_type in objects(self.data, node, rdf::type_)? {
+            if node_type == *class
+                || self.closure(&rdfs::subClassOf.into_term(), &node_type, false)?.contains(class)
+            {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    fn matches_pattern(
+        &self,
+        value: &Node,
+        pattern: &str,
+        flags: &str,
+    ) -> Result<bool, Box<dyn Error + Send + Sync>> {
+        let text = match value.kind() {
+            TermKind::Iri => value.iri().unwrap().as_str().to_string(),
+            TermKind::Literal => value.lexical_form().unwrap().to_string(),
+            _ => return Ok(false),
+        };
+        let mut patterns = self.patterns.borrow_mut();
+        let key = (pattern.to_string(), flags.to_string());
+        if !patterns.contains_key(&key) {
+            let regex = RegexBuilder::new(pattern)
+                .case_insensitive(flags.contains('i'))
+                .dot_matches_new_line(flags.contains('s'))
+                .multi_line(flags.contains('m'))
+                .ignore_whitespace(flags.contains('x'))
+                .build()
+                .map_err(|err| invalid(format!("Invalid sh:pattern {:?}: {}", pattern, err)))?;
+            patterns.insert(key.clone(), regex);
+        }
+        Ok(patterns[&key].is_match(&text))
+    }
+
+    fn integer(
+        &self,
+        shape: &Node,
+        predicate: NsTerm,
+    ) -> Result<Option<i64>, Box<dyn Error + Send + Sync>> {
+        match object(self.shapes, shape, predicate)? {
+            Some(value) => {
+                let integer = value.lexical_form().and_then(|lexical| lexical.parse().ok());
+                let integer = integer.ok_or_else(|| {
+                    invalid(format!("Expected an integer literal, found {}", display(&value)))
+                })?;
+                Ok(Some(integer))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn is_true(
+        &self,
+        shape: &Node,
+        predicate: NsTerm,
+    ) -> Result<bool, Box<dyn Error + Send + Sync>> {
+        Ok(object(self.shapes, shape, predicate)?
+            .is_some_and(|value| value.lexical_form().is_some_and(|l| l == "true" || l == "1")))
+    }
+}
+
+/// Builds the `sh:ValidationReport` graph of `results`, as returned by SHACL processors.
+///
+/// Complex paths of the shapes graph are copied into the report.
+fn report_graph(
+    results: &[ShapeResult],
+    shapes: &LightGraph,
+) -> Result<LightGraph, Box<dyn Error + Send + Sync>> {
+    let mut graph = LightGraph::new();
+    let report: Node = BnodeId::new_unchecked("report".to_string()).into_term();
+    graph.insert(&report, rdf::type_, sh::ValidationReport)?;
+    graph.insert(&report, sh::conforms, results.is_empty().to_string().as_str() * xsd::boolean)?;
+
+    for (index, result) in results.iter().enumerate() {
+        let node: Node = BnodeId::new_unchecked(format!("result{}", index)).into_term();
+        graph.insert(&report, sh::result, &node)?;
+        graph.insert(&node, rdf::type_, sh::ValidationResult)?;
+        graph.insert(&node, sh::focusNode, &result.focus_node)?;
+        if let Some(path) = &result.path {
+            graph.insert(&node, sh::resultPath, path)?;
+            if path.is_blank_node() {
+                copy_description(shapes, path, &mut graph)?;
+            }
+        }
+        if let Some(value) = &result.value {
+            graph.insert(&node, sh::value, value)?;
+        }
+        graph.insert(&node, sh::resultSeverity, &result.severity)?;
+        graph.insert(&node, sh::sourceConstraintComponent, &result.component)?;
+        graph.insert(&node, sh::sourceShape, &result.shape)?;
+        graph.insert(&node, sh::resultMessage, result.message.as_str())?;
+    }
+    Ok(graph)
+}
+
+/// Copies the triples describing the blank node `node`, recursively, from `source` to `target`.
+fn copy_description(
+    source: &LightGraph,
+    node: &Node,
+    target: &mut LightGraph,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    for triple in source.triples_matching([node], Any, Any) {
+        let [subject, predicate, object] = triple?;
+        if target.insert(subject, predicate, object)? && object.is_blank_node() {
+            copy_description(source, &object.clone().into_term(), target)?;
+        }
+    }
+    Ok(())
+}
+
+fn objects<T: Term>(
+    graph: &LightGraph,
+    subject: &Node,
+    predicate: T,
+) -> Result<Vec<Node>, Box<dyn Error + Send + Sync>> {
+    let mut objects = Vec::new();
+    for triple in graph.triples_matching([subject], [predicate], Any) {
+        objects.push(triple?[2].clone().into_term());
+    }
+    Ok(objects)
+}
+
+fn object<T: Term>(
+    graph: &LightGraph,
+    subject: &Node,
+    predicate: T,
+) -> Result<Option<Node>, Box<dyn Error + Send + Sync>> {
+    Ok(objects(graph, subject, predicate)?.into_iter().next())
+}
+
+fn subjects<T: Term, M: TermMatcher>(
+    graph: &LightGraph,
+    predicate: T,
+    object: M,
+) -> Result<Vec<Node>, Box<dyn Error + Send + Sync>> {
+    let mut subjects = Vec::new();
+    for triple in graph.triples_matching(Any, [predicate], object) {
+        subjects.push(triple?[0].clone().into_term());
+    }
+    Ok(subjects)
+}
+
+fn has_triple<T: Term, M: TermMatcher>(
+    graph: &LightGraph,
+    subject: &Node,
+    predicate: T,
+    object: M,
+) -> Result<bool, Box<dyn Error + Send + Sync>> {
+    Ok(graph.triples_matching([subject], [predicate], object).next().transpose()?.is_some())
+}
+
+/// Returns the members of the RDF list starting at `head`.
+fn rdf_list(graph: &LightGraph, head: &Node) -> Result<Vec<Node>, Box<dyn Error + Send + Sync>> {
+    let mut members = Vec::new();
+    let mut seen = HashSet::new();
+    let mut current = head.clone();
+    while !Term::eq(&current, rdf::nil) {
+        if !seen.insert(current.clone()) {
+            return Err(invalid("Cyclic RDF list in the shapes graph"));
+        }
+        members.extend(object(graph, &current, rdf::first)?);
+        current = object(graph, &current, rdf::rest)?
+            .ok_or_else(|| invalid("Unterminated RDF list in the shapes graph"))?;
+    }
+    Ok(members)
+}
+
+/// Error for shapes that cannot be evaluated.
+fn invalid(message: impl Into<String>) -> Box<dyn Error + Send + Sync> {
+    Box::new(InvalidShapes(message.into()))
+}
+
+/// Removes duplicates, keeping the first occurrences in order.
+fn dedup(nodes: &mut Vec<Node>) {
+    let mut seen = HashSet::new();
+    nodes.retain(|node| seen.insert(node.clone()));
+}
+
+fn display(node: &Node) -> String {
+    match node.kind() {
+        TermKind::Iri => format!("<{}>", node.iri().unwrap().as_str()),
+        TermKind::BlankNode => format!("_:{}", node.bnode_id().unwrap().as_str()),
+        TermKind::Literal => format!("\"{}\"", node.lexical_form().unwrap()),
+        _ => format!("{:?}", node),
+    }
+}
+
+fn has_node_kind(value: &Node, node_kind: &Node) -> bool {
+    let kinds: &[NsTerm] = match value.kind() {
+        TermKind::Iri => &[sh::IRI, sh::BlankNodeOrIRI, sh::IRIOrLiteral],
+        TermKind::BlankNode => &[sh::BlankNode, sh::BlankNodeOrIRI, sh::BlankNodeOrLiteral],
+        TermKind::Literal => &[sh::Literal, sh::BlankNodeOrLiteral, sh::IRIOrLiteral],
+        _ => &[],
+    };
+    kinds.iter().any(|kind| Term::eq(node_kind, *kind))
+}
+
+/// Number of characters of the lexical form or IRI of `value`, blank nodes have none.
+fn string_length(value: &Node) -> Option<i64> {
+    match value.kind() {
+        TermKind::Iri => Some(value.iri().unwrap().as_str().chars().count() as i64),
+        TermKind::Literal => Some(value.lexical_form().unwrap().chars().count() as i64),
+        _ => None,
+    }
+}
+
+const INTEGER_DATATYPES: [&str; 13] = [
+    "integer",
+    "nonNegativeInteger",
+    "positiveInteger",
+    "nonPositiveInteger",
+    "negativeInteger",
+    "long",
+    "int",
+    "short",
+    "byte",
+    "unsignedLong",
+    "unsignedInt",
+    "unsignedShort",
+    "unsignedByte",
+];
+
+/// Local name of `datatype` in the XML Schema namespace, if it is in it.
+fn xsd_local_name(datatype: &str) -> Option<&str> {
+    datatype.strip_prefix(xsd::PREFIX.as_str())
+}
+
+fn is_numeric(datatype: &str) -> bool {
+    xsd_local_name(datatype).is_some_and(|name| {
+        INTEGER_DATATYPES.contains(&name) || ["decimal", "double", "float"].contains(&name)
+    })
+}
+
+/// Whether `value` is a literal of `datatype` with a valid lexical form.
+fn has_datatype(value: &Node, datatype: &Node) -> bool {
+    let (Some(value_datatype), Some(expected)) = (value.datatype(), datatype.iri()) else {
+        return false;
+    };
+    if value_datatype.as_str() != expected.as_str() {
+        return false;
+    }
+    let lexical_form = value.lexical_form().unwrap();
+    match xsd_local_name(expected.as_str()) {
+        Some(name) if INTEGER_DATATYPES.contains(&name) => lexical_form.parse::<i128>().is_ok(),
+        Some("decimal") => {
+            !lexical_form.contains(['e', 'E']) && lexical_form.parse::<f64>().is_ok()
+        }
+        Some("double") | Some("float") => {
+            ["INF", "-INF", "NaN"].contains(&lexical_form.as_ref())
+                || lexical_form.parse::<f64>().is_ok()
+        }
+        Some("boolean") => ["true", "false", "1", "0"].contains(&lexical_form.as_ref()),
+        _ => true,
+    }
+}
+
+/// Compares two literals: numerically if both are numeric, otherwise by lexical form
+/// if they have the same datatype, as for dates. Other values are incomparable.
+fn compare_literals(value: &Node, bound: &Node) -> Option<Ordering> {
+    let (value_datatype, bound_datatype) = (value.datatype()?, bound.datatype()?);
+    let (value_lexical, bound_lexical) = (value.lexical_form()?, bound.lexical_form()?);
+    if is_numeric(value_datatype.as_str()) && is_numeric(bound_datatype.as_str()) {
+        let value_number: f64 = value_lexical.parse().ok()?;
+        let bound_number: f64 = bound_lexical.parse().ok()?;
+        value_number.partial_cmp(&bound_number)
+    } else if value_datatype.as_str() == bound_datatype.as_str() {
+        Some(Ord::cmp(value_lexical.as_ref(), bound_lexical.as_ref()))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use catplus_common::rdf::rdf_parser::parse_turtle_to_graph;
+
+    const SHAPES: &str = r#"
+        @prefix sh: <http://www.w3.org/ns/shacl#> .
+        @prefix xsd: <http://www.w3.org/2001/XMLSchema#> .
+        @prefix ex: <http://example.org/> .
+
+        ex:PersonShape a sh:NodeShape ;
+            sh:targetClass ex:Person ;
+            sh:property [
+                sh:path ex:name ;
+                sh:minCount 1 ;
+                sh:maxCount 1 ;
+                sh:datatype xsd:string ;
+                sh:pattern "^[A-Z]" ;
+            ] , [
+                sh:path ex:age ;
+                sh:datatype xsd:integer ;
+                sh:minInclusive 0 ;
+            ] , [
+                sh:path ex:status ;
+                sh:in ( "active" "retired" ) ;
+            ] , [
+                sh:path ( ex:employer ex:country ) ;
+                sh:class ex:Country ;
+            ] , [
+                sh:path [ sh:inversePath ex:knows ] ;
+                sh:node ex:PersonShape ;
+            ] .
+    "#;
+
+    fn validate(data: &str) -> ValidationReport {
+        let data = parse_turtle_to_graph(data).unwrap();
+        let shapes = parse_turtle_to_graph(SHAPES).unwrap();
+        NativeShaclEngine::new().validate(&data, Some(&shapes)).unwrap()
+    }
+
+    fn components(report: &ValidationReport) -> Vec<String> {
+        let mut components: Vec<String> = report
+            .results
+            .iter()
+            .map(|r| r.source_constraint_component.as_ref().unwrap().iri().unwrap().to_string())
+            .collect();
+        components.sort();
+        components
+    }
+
+    #[test]
+    fn test_conforming_data() {
+        let report = validate(
+            r#"
+            @prefix ex: <http://example.org/> .
+            ex:alice a ex:Person ; ex:name "Alice" ; ex:age 42 ; ex:status "active" ;
+                ex:employer ex:sdsc ; ex:knows ex:bob .
+            ex:sdsc ex:country ex:switzerland .
+            ex:switzerland a ex:Country .
+            ex:bob a ex:Person ; ex:name "Bob" .
+            "#,
+        );
+        assert!(report.conforms, "{:?}", components(&report));
+        assert!(report.results.is_empty());
+    }
+
+    #[test]
+    fn test_violating_data() {
+        let report = validate(
+            r#"
+            @prefix ex: <http://example.org/> .
+            ex:alice a ex:Person ; ex:name "alice", "Alicia" ; ex:age -1 ; ex:status "unknown" ;
+                ex:employer ex:sdsc .
+            ex:sdsc ex:country ex:atlantis .
+            ex:bob a ex:Person ; ex:knows ex:alice ; ex:age "old" .
+            "#,
+        );
+        assert!(!report.conforms);
+        let components: Vec<String> = components(&report)
+            .iter()
+            .map(|c| c.trim_start_matches("http://www.w3.org/ns/shacl#").to_string())
+            .collect();
+        assert_eq!(
+            components,
+            vec![
+                "ClassConstraintComponent",
+                "DatatypeConstraintComponent",
+                "InConstraintComponent",
+                "MaxCountConstraintComponent",
+                "MinCountConstraintComponent",
+                "MinInclusiveConstraintComponent",
+                "MinInclusiveConstraintComponent",
+                "NodeConstraintComponent",
+                "PatternConstraintComponent",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_report_contains_complex_paths() {
+        let report = validate(
+            r#"
+            @prefix ex: <http://example.org/> .
+            ex:alice a ex:Person ; ex:name "Alice" ; ex:employer ex:sdsc .
+            ex:sdsc ex:country ex:atlantis .
+            "#,
+        );
+        let path =
+            report.graph.triples_matching(Any, [sh::resultPath], Any).next().unwrap().unwrap()[2]
+                .clone();
+        assert!(report.graph.triples_matching([path], [rdf::first], Any).next().is_some());
+    }
+
+    #[test]
+    fn test_value_and_logical_constraints() {
+        let shapes = parse_turtle_to_graph(
+            r#"
+            @prefix sh: <http://www.w3.org/ns/shacl#> .
+            @prefix ex: <http://example.org/> .
+
+            ex:NamedShape sh:property [ sh:path ex:name ; sh:minCount 1 ] .
+            ex:AgedShape sh:property [ sh:path ex:age ; sh:minCount 1 ] .
+            ex:PersonShape a sh:NodeShape ;
+                sh:targetClass ex:Person ;
+                sh:property [ sh:path ex:role ; sh:hasValue ex:member ] ;
+                sh:and ( ex:NamedShape ex:AgedShape ) ;
+                sh:or ( ex:NamedShape ex:AgedShape ) ;
+                sh:xone ( ex:NamedShape ex:AgedShape ) ;
+                sh:not [ sh:property [ sh:path ex:banned ; sh:minCount 1 ] ] .
+            "#,
+        )
+        .unwrap();
+        let validate = |data: &str| {
+            let data = parse_turtle_to_graph(data).unwrap();
+            let report = NativeShaclEngine::new().validate(&data, Some(&shapes)).unwrap();
+            let components: Vec<String> = components(&report)
+                .iter()
+                .map(|c| c.trim_start_matches("http://www.w3.org/ns/shacl#").to_string())
+                .collect();
+            components
+        };
+
+        // Named and aged: sh:and holds but sh:xone does not.
+        assert_eq!(
+            validate(
+                r#"
+                @prefix ex: <http://example.org/> .
+                ex:alice a ex:Person ; ex:role ex:member ; ex:name "Alice" ; ex:age 42 .
+                "#
+            ),
+            vec!["XoneConstraintComponent"]
+        );
+        assert_eq!(
+            validate(
+                r#"
+                @prefix ex: <http://example.org/> .
+                ex:bob a ex:Person ; ex:role ex:guest ; ex:banned true .
+                "#
+            ),
+            vec![
+                "AndConstraintComponent",
+                "HasValueConstraintComponent",
+                "NotConstraintComponent",
+                "OrConstraintComponent",
+                "XoneConstraintComponent",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unsupported_constraint() {
+        let data = LightGraph::new();
+        let shapes = parse_turtle_to_graph(
+            r#"
+            @prefix sh: <http://www.w3.org/ns/shacl#> .
+            @prefix ex: <http://example.org/> .
+            ex:PersonShape sh:targetClass ex:Person ;
+                sh:property [ sh:path ex:name ; sh:uniqueLang true ] .
+            "#,
+        )
+        .unwrap();
+        let err = NativeShaclEngine::new().validate(&data, Some(&shapes)).unwrap_err();
+        assert!(err.is::<InvalidShapes>());
+        assert!(err.to_string().contains("Unsupported constraint"), "{}", err);
+
+        // Open shapes are supported.
+        let shapes = parse_turtle_to_graph(
+            r#"
+            @prefix sh: <http://www.w3.org/ns/shacl#> .
+            @prefix ex: <http://example.org/> .
+            ex:PersonShape sh:targetClass ex:Person ; sh:closed false .
+            "#,
+        )
+        .unwrap();
+        assert!(NativeShaclEngine::new().validate(&data, Some(&shapes)).unwrap().conforms);
+    }
+
+    #[test]
+    fn test_missing_shapes() {
+        let data = LightGraph::new();
+        assert!(NativeShaclEngine::new().validate(&data, None).is_err());
+    }
+}
@@ -6,7 +6,9 @@ use sophia_api::prelude::*;
 use std::error::Error;
 
 use crate::core::{ShaclEngine, ValidationReport};
-use catplus_common::rdf::rdf_serializers::serialize_graph_to_turtle;
+use catplus_common::{
+    graph::prefix_map::generate_prefix_map, rdf::rdf_serializers::serialize_graph_to_turtle,
+};
 
 pub struct ShaclApiEndpoint {
     url: String,
@@ -31,13 +33,13 @@ impl ShaclEngine for ShaclApiEndpoint {
         &self,
         data: &LightGraph,
         shapes: Option<&LightGraph>,
-    ) -> Result<ValidationReport, Box<dyn Error>> {
+    ) -> Result<ValidationReport, Box<dyn Error + Send + Sync>> {
         let url = format!("{}/validate", self.url);
         // Request report in turtle format
         let accept_header = "text/turtle";
 
         // Serialize data graph and add to multipart form
-        let data_bytes = serialize_graph_to_turtle(data).unwrap().into_bytes();
+        let data_bytes = serialize_graph_to_turtle(data, &generate_prefix_map())?.into_bytes();
         let data_part =
             multipart::Part::bytes(data_bytes).file_name("data.ttl").mime_str("text/turtle")?;
 
@@ -45,7 +47,8 @@ impl ShaclEngine for ShaclApiEndpoint {
 
         // If shapes are provided, serialize them and add to form
         if let Some(shapes) = shapes {
-            let shapes_bytes = serialize_graph_to_turtle(shapes).unwrap().into_bytes();
+            let shapes_bytes =
+                serialize_graph_to_turtle(shapes, &generate_prefix_map())?.into_bytes();
             let shapes_part = multipart::Part::bytes(shapes_bytes)
                 .file_name("shapes.ttl")
                 .mime_str("text/turtle")?;
@@ -55,10 +58,12 @@ impl ShaclEngine for ShaclApiEndpoint {
 
         let client = Client::new();
         let response = client.post(url).header("Accept", accept_header).multipart(form).send()?;
+        // An error status has no report in its body
+        let response = response.error_for_status()?;
 
         let report_graph = turtle::parse_str(&response.text()?).collect_triples()?;
 
-        Ok(ValidationReport::from_graph(report_graph))
+        ValidationReport::from_graph(report_graph)
     }
 }
 
@@ -1,18 +1,31 @@
-use anyhow::{Context, Result};
-use catplus_common::rdf::{
-    rdf_parser::parse_turtle_to_graph, rdf_serializers::serialize_graph_to_turtle,
+use anyhow::{anyhow, Context};
+use catplus_common::{
+    graph::skolem::deskolemize_graph,
+    rdf::rdf_parser::{parse_to_graph, RdfSyntax},
 };
 use clap::Parser;
 use std::{
+    error::Error,
     fs,
     fs::File,
     io::{stdin, stdout, BufReader, BufWriter, Read, Write},
     path::{Path, PathBuf},
+    process::ExitCode,
+};
+use validation::{
+    core::*,
+    engines::{native::NativeShaclEngine, shacl_api::*},
+    report::{render_report, summary_line, ReportFormat},
 };
-use validation::{core::*, engines::shacl_api::*};
 
 // Validates an RDF file
-// Only turtle format is supported
+// Turtle, N-Triples, JSON-LD and RDF/XML are supported
+//
+// Exit codes:
+// 0: the data conforms, or only has results below the `--fail-on` severity
+// 1: the data has results at or above the `--fail-on` severity
+// 2: the data, the shapes or the options are invalid, or the report cannot be written
+// 3: the validation engine is not available, or failed
 #[derive(Parser, Debug)]
 struct Args {
     /// Path to the input RDF data.
@@ -31,68 +44,258 @@ struct Args {
     shapes: Option<PathBuf>,
 
     /// Endpoint of the SHACL API server.
+    /// If not provided, the shapes are evaluated by the native engine, which requires `--shapes`.
     #[arg(short, long)]
-    endpoint: String,
+    endpoint: Option<String>,
+
+    /// RDF syntax of the input data.
+    /// Guessed from the file extension or the content if not provided.
+    #[arg(long, value_enum)]
+    input_format: Option<RdfSyntax>,
+
+    /// Turn the skolem IRIs of materialized data back into blank nodes before validating,
+    /// so that shapes expecting blank nodes apply.
+    #[arg(long, default_value_t = false)]
+    deskolemize: bool,
+
+    /// Format of the validation report.
+    /// The SHACL report graph in turtle, or the results grouped by type of the focus nodes.
+    #[arg(long, value_enum, default_value = "turtle")]
+    report_format: ReportFormat,
+
+    /// Lowest severity of the results that fails the validation.
+    /// Results of lower severity are still reported, with exit code 0.
+    #[arg(long, value_enum, default_value = "violation")]
+    fail_on: FailOn,
+}
+
+/// Severity threshold of `--fail-on`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum FailOn {
+    Warning,
+    Violation,
+}
+
+impl From<FailOn> for Severity {
+    fn from(fail_on: FailOn) -> Self {
+        match fail_on {
+            FailOn::Warning => Severity::Warning,
+            FailOn::Violation => Severity::Violation,
+        }
+    }
+}
+
+/// Why the validation could not complete, which determines the exit code.
+enum Failure {
+    /// The data, the shapes or the options are invalid, or the report cannot be written.
+    InvalidInput(anyhow::Error),
+    /// The validation engine is not available, or failed to validate.
+    Engine(anyhow::Error),
 }
 
-fn main() -> Result<()> {
+impl From<anyhow::Error> for Failure {
+    fn from(err: anyhow::Error) -> Self {
+        Failure::InvalidInput(err)
+    }
+}
+
+fn main() -> ExitCode {
     let args = Args::parse();
+    let threshold = Severity::from(args.fail_on);
 
-    validate_graph(args.input, args.output, args.shapes, args.endpoint)?;
+    match validate_graph(args) {
+        Ok(report) => {
+            if !report.conforms {
+                eprintln!("Graph does not conform to the shapes ({}).", summary_line(&report));
+            }
+            ExitCode::from(report_exit_code(&report, threshold))
+        }
+        Err(failure) => {
+            let (Failure::InvalidInput(err) | Failure::Engine(err)) = &failure;
+            eprintln!("Error: {:#}", err);
+            ExitCode::from(failure.exit_code())
+        }
+    }
+}
 
-    Ok(())
+/// Classifies an error of the validation engine: shapes it cannot evaluate are invalid input.
+fn engine_failure(err: Box<dyn Error + Send + Sync>) -> Failure {
+    let invalid_shapes = err.is::<InvalidShapes>();
+    let err = anyhow!(err).context("Failed to validate the graph");
+    if invalid_shapes {
+        Failure::InvalidInput(err)
+    } else {
+        Failure::Engine(err)
+    }
 }
 
-// Get a reader based on input path, either from stdin or a file.
-pub fn get_reader(path: &Path) -> Result<Box<dyn Read>> {
-    return match path.to_str().unwrap() {
-        "-" => Ok(Box::new(BufReader::new(stdin()))),
-        path => Ok(Box::new(BufReader::new(File::open(path)?))),
-    };
+impl Failure {
+    fn exit_code(&self) -> u8 {
+        match self {
+            Failure::InvalidInput(_) => 2,
+            Failure::Engine(_) => 3,
+        }
+    }
 }
 
-// Get a writer based on input path, either to stdout or a file.
-pub fn get_writer(path: &Path) -> Result<Box<dyn Write>> {
-    return match path.to_str().unwrap() {
-        "-" => Ok(Box::new(BufWriter::new(stdout()))),
-        path => Ok(Box::new(BufWriter::new(File::create(path)?))),
-    };
+/// Exit code of a completed validation: 1 if a result reaches `threshold`, 0 otherwise.
+fn report_exit_code(report: &ValidationReport, threshold: Severity) -> u8 {
+    if report.conforms {
+        return 0;
+    }
+    // A report without parsed results still fails, as its severity is unknown.
+    match report.max_severity() {
+        Some(severity) if severity < threshold => 0,
+        _ => 1,
+    }
 }
 
-fn validate_graph(
-    input: PathBuf,
-    output: PathBuf,
-    shapes: Option<PathBuf>,
-    endpoint: String,
-) -> Result<()> {
-    // Check if the endpoint is reachable
-    let shacl_api = ShaclApiEndpoint::new(endpoint.clone());
-    if !shacl_api.is_available() {
-        return Err(anyhow::anyhow!("SHACL API is not available at {}", endpoint));
+// Get a reader based on input path, either from stdin or a file.
+pub fn get_reader(path: &Path) -> anyhow::Result<Box<dyn Read>> {
+    if path == Path::new("-") {
+        return Ok(Box::new(BufReader::new(stdin())));
+    }
+    let file = File::open(path)
+        .with_context(|| format!("Failed to open input file '{}'.", path.display()))?;
+    Ok(Box::new(BufReader::new(file)))
+}
+
+// Get a writer based on input path, either to stdout or a file.
+pub fn get_writer(path: &Path) -> anyhow::Result<Box<dyn Write>> {
+    if path == Path::new("-") {
+        return Ok(Box::new(BufWriter::new(stdout())));
     }
+    let file = File::create(path)
+        .with_context(|| format!("Failed to create output file '{}'.", path.display()))?;
+    Ok(Box::new(BufWriter::new(file)))
+}
 
-    // Parse I/O paths
-    let mut source = get_reader(&input)?;
-    let mut sink = get_writer(&output)?;
+fn validate_graph(args: Args) -> Result<ValidationReport, Failure> {
+    let engine: Box<dyn ShaclEngine> = match args.endpoint {
+        Some(endpoint) => {
+            // Check if the endpoint is reachable
+            let shacl_api = ShaclApiEndpoint::new(endpoint.clone());
+            if !shacl_api.is_available() {
+                return Err(Failure::Engine(anyhow!("SHACL API is not available at {}", endpoint)));
+            }
+            Box::new(shacl_api)
+        }
+        None if args.shapes.is_none() => {
+            return Err(anyhow!("The native engine requires --shapes, or use --endpoint").into());
+        }
+        None => Box::new(NativeShaclEngine::new()),
+    };
 
     // Read whole files as strings
     let mut input_data = String::new();
-    source.read_to_string(&mut input_data).context("Failed to read input data")?;
-
-    let shapes_data =
-        shapes.map(|path| fs::read_to_string(path).expect("Failed to read shapes file"));
+    get_reader(&args.input)?
+        .read_to_string(&mut input_data)
+        .context("Failed to read input data")?;
 
     // Parse into triple graphs
-    let data_graph =
-        parse_turtle_to_graph(&input_data).context("Failed to parse input RDF data")?;
+    let input_format = args.input_format.or_else(|| RdfSyntax::from_path(&args.input));
+    let mut data_graph =
+        parse_to_graph(&input_data, input_format).context("Failed to parse input RDF data")?;
+    if args.deskolemize {
+        data_graph = deskolemize_graph(&data_graph).context("Failed to deskolemize input data")?;
+    }
 
-    let shapes_graph =
-        shapes_data.map(|data| parse_turtle_to_graph(&data).expect("Failed to parse shapes data"));
+    let shapes_graph = match &args.shapes {
+        Some(path) => {
+            let data = fs::read_to_string(path)
+                .with_context(|| format!("Failed to read shapes file '{}'.", path.display()))?;
+            let graph = parse_to_graph(&data, RdfSyntax::from_path(path))
+                .with_context(|| format!("Failed to parse shapes file '{}'.", path.display()))?;
+            Some(graph)
+        }
+        None => None,
+    };
 
-    let report = shacl_api.validate(&data_graph, shapes_graph.as_ref()).unwrap();
+    let report = engine.validate(&data_graph, shapes_graph.as_ref()).map_err(engine_failure)?;
 
     // Write the validation report to the output
-    sink.write_all(serialize_graph_to_turtle(&report.graph).unwrap().as_bytes())
-        .context("Failed to write to output file")?;
-    Ok(())
+    let rendered = render_report(
+        &report,
+        args.report_format,
+        args.fail_on.into(),
+        &data_graph,
+        shapes_graph.as_ref(),
+    )?;
+    let mut sink = get_writer(&args.output)?;
+    sink.write_all(rendered.as_bytes()).context("Failed to write to output file")?;
+    sink.flush().context("Failed to write to output file")?;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use catplus_common::rdf::rdf_parser::parse_turtle_to_graph;
+    use sophia::inmem::graph::LightGraph;
+
+    fn report(severities: &[&str]) -> ValidationReport {
+        let results: Vec<String> = severities
+            .iter()
+            .map(|severity| {
+                format!(
+                    "[ a sh:ValidationResult ; sh:focusNode ex:alice ; sh:resultSeverity sh:{} ]",
+                    severity
+                )
+            })
+            .collect();
+        let mut turtle = String::from(
+            "@prefix sh: <http://www.w3.org/ns/shacl#> . @prefix ex: <http://example.org/> . ",
+        );
+        turtle.push_str(&format!("[] a sh:ValidationReport ; sh:conforms {}", results.is_empty()));
+        if !results.is_empty() {
+            turtle.push_str(&format!(" ; sh:result {}", results.join(" , ")));
+        }
+        turtle.push_str(" .");
+        ValidationReport::from_graph(parse_turtle_to_graph(&turtle).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_report_exit_code() {
+        let cases = [
+            (vec![], 0, 0),
+            (vec!["Info"], 0, 0),
+            (vec!["Warning"], 1, 0),
+            (vec!["Violation"], 1, 1),
+            (vec!["Info", "Warning"], 1, 0),
+            (vec!["Warning", "Violation"], 1, 1),
+        ];
+        for (severities, on_warning, on_violation) in cases {
+            let report = report(&severities);
+            assert_eq!(
+                report_exit_code(&report, Severity::Warning),
+                on_warning,
+                "{:?}",
+                severities
+            );
+            assert_eq!(
+                report_exit_code(&report, Severity::Violation),
+                on_violation,
+                "{:?}",
+                severities
+            );
+        }
+
+        // Without parsed results, a non-conforming report fails at any threshold.
+        let report = ValidationReport::new(false, LightGraph::new());
+        assert_eq!(report_exit_code(&report, Severity::Warning), 1);
+        assert_eq!(report_exit_code(&report, Severity::Violation), 1);
+    }
+
+    #[test]
+    fn test_failure_exit_code() {
+        assert_eq!(Failure::InvalidInput(anyhow!("invalid")).exit_code(), 2);
+        assert_eq!(Failure::Engine(anyhow!("unavailable")).exit_code(), 3);
+
+        let failure = engine_failure(Box::new(InvalidShapes("Unsupported constraint".into())));
+        assert_eq!(failure.exit_code(), 2);
+        let failure = engine_failure("Connection refused".into());
+        assert_eq!(failure.exit_code(), 3);
+        let (Failure::InvalidInput(err) | Failure::Engine(err)) = failure;
+        assert_eq!(format!("{:#}", err), "Failed to validate the graph: Connection refused");
+    }
 }
@@ -0,0 +1,603 @@
+/// Rendering of validation reports for people and CI tools.
+use anyhow::{Context, Result};
+use catplus_common::{
+    graph::{namespaces::sh, prefix_map::generate_prefix_map},
+    rdf::rdf_serializers::serialize_graph_to_turtle,
+};
+use serde_json::json;
+use sophia::{api::ns::rdf, inmem::graph::LightGraph};
+use sophia_api::{
+    ns::NsTerm,
+    prelude::*,
+    term::{SimpleTerm, TermKind},
+};
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::core::{Severity, ValidationReport, ValidationResult};
+
+/// Label of the group of focus nodes without `rdf:type` in the data graph.
+const UNTYPED: &str = "(untyped)";
+
+/// Formats of the validation report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ReportFormat {
+    /// The SHACL report graph.
+    Turtle,
+    Text,
+    Json,
+    Markdown,
+    /// JUnit XML, with one test case per shape.
+    Junit,
+}
+
+/// Renders `report` in `format`.
+///
+/// The results are grouped by the types of their focus node in `data`. The `shapes` are used
+/// to list the shapes without results as passing test cases in JUnit, where the shapes with
+/// results of severity `fail_on` or higher are failures.
+pub fn render_report(
+    report: &ValidationReport,
+    format: ReportFormat,
+    fail_on: Severity,
+    data: &LightGraph,
+    shapes: Option<&LightGraph>,
+) -> Result<String> {
+    let renderer = Renderer::new(data, shapes, fail_on);
+    match format {
+        ReportFormat::Turtle => serialize_graph_to_turtle(&report.graph, &generate_prefix_map())
+            .context("Failed to serialize validation report"),
+        ReportFormat::Text => Ok(renderer.text(report)),
+        ReportFormat::Json => serde_json::to_string_pretty(&renderer.json(report))
+            .map(|json| json + "\n")
+            .context("Failed to serialize validation report"),
+        ReportFormat::Markdown => Ok(renderer.markdown(report)),
+        ReportFormat::Junit => Ok(renderer.junit(report)),
+    }
+}
+
+/// One line summary of the result counts, e.g. "2 violations, 1 warning, 0 infos".
+pub fn summary_line(report: &ValidationReport) -> String {
+    Severity::ALL
+        .iter()
+        .map(|severity| {
+            let count = report.count(*severity);
+            let name = format!("{:?}", severity).to_lowercase();
+            format!("{} {}{}", count, name, if count == 1 { "" } else { "s" })
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Groups of results, keyed by the types of their focus node.
+type Groups<'r> = BTreeMap<String, Vec<&'r ValidationResult>>;
+
+struct Renderer<'a> {
+    data: &'a LightGraph,
+    shapes: Option<&'a LightGraph>,
+    /// Lowest severity of the results that fail a JUnit test case.
+    fail_on: Severity,
+    /// Prefixes and the namespaces they abbreviate.
+    prefixes: Vec<(String, String)>,
+}
+
+impl<'a> Renderer<'a> {
+    fn new(data: &'a LightGraph, shapes: Option<&'a LightGraph>, fail_on: Severity) -> Self {
+        let mut prefixes: Vec<(String, String)> = generate_prefix_map()
+            .iter()
+            .map(|(prefix, namespace)| {
+                (prefix.as_str().to_string(), namespace.as_str().to_string())
+            })
+            .collect();
+        prefixes.push(("sh".to_string(), sh::ns.get("").unwrap().iri().unwrap().to_string()));
+        Renderer { data, shapes, fail_on, prefixes }
+    }
+
+    /// Short form of `term`: prefixed name, `<iri>`, `_:id` or quoted literal.
+    fn display<T: Term>(&self, term: T) -> String {
+        match term.kind() {
+            TermKind::Iri => {
+                let iri = term.iri().unwrap();
+                self.prefixes
+                    .iter()
+                    .filter(|(_, namespace)| iri.as_str().starts_with(namespace.as_str()))
+                    .max_by_key(|(_, namespace)| namespace.len())
+                    .map(|(prefix, namespace)| format!("{}:{}", prefix, &iri[namespace.len()..]))
+                    .unwrap_or_else(|| format!("<{}>", iri.as_str()))
+            }
+            TermKind::BlankNode => format!("_:{}", term.bnode_id().unwrap().as_str()),
+            TermKind::Literal => format!("\"{}\"", term.lexical_form().unwrap()),
+            _ => format!("{:?}", term),
+        }
+    }
+
+    fn display_option(&self, term: Option<&SimpleTerm>) -> String {
+        term.map_or_else(String::new, |term| self.display(term))
+    }
+
+    /// Name of a constraint component without the `sh:` namespace and suffix, e.g. "MinCount".
+    fn component_name(&self, result: &ValidationResult) -> String {
+        let component = self.display_option(result.source_constraint_component.as_ref());
+        let component = component.strip_prefix("sh:").unwrap_or(&component);
+        component.strip_suffix("ConstraintComponent").unwrap_or(component).to_string()
+    }
+
+    fn message(&self, result: &ValidationResult) -> String {
+        result
+            .message
+            .clone()
+            .unwrap_or_else(|| format!("{} constraint", self.component_name(result)))
+    }
+
+    /// Types of `node` in the data graph, sorted and joined.
+    fn node_types(&self, node: &SimpleTerm) -> String {
+        let types: BTreeSet<String> = self
+            .data
+            .triples_matching([node], [rdf::type_], Any)
+            .filter_map(|t| t.ok().map(|t| self.display(t[2])))
+            .collect();
+        if types.is_empty() {
+            UNTYPED.to_string()
+        } else {
+            types.into_iter().collect::<Vec<_>>().join(", ")
+        }
+    }
+
+    fn groups<'r>(&self, report: &'r ValidationReport) -> Groups<'r> {
+        let mut groups = Groups::new();
+        for result in &report.results {
+            groups.entry(self.node_types(&result.focus_node)).or_default().push(result);
+        }
+        groups
+    }
+
+    /// Test case name of the source shape of `result`, whose path is described in `graph`:
+    /// IRI shapes by name, property shapes by their node shape and path when the shapes
+    /// graph tells, otherwise by path.
+    ///
+    /// Blank node property shapes are found by path rather than by label, since the labels
+    /// of a report parsed from a SHACL API server do not match those of the shapes graph.
+    fn shape_name(&self, result: &ValidationResult, graph: &LightGraph) -> String {
+        let Some(shape) = &result.source_shape else {
+            return "(unknown shape)".to_string();
+        };
+        if shape.is_iri() {
+            return self.display(shape);
+        }
+        let path = result
+            .result_path
+            .as_ref()
+            .map_or_else(|| "(no path)".to_string(), |path| self.display_path(path, graph));
+        // Among the node shapes with a property shape of this path, those targeting a type
+        // of the focus node are preferred.
+        let parents = self.parent_shapes(&path);
+        let parent = parents
+            .iter()
+            .find(|parent| self.targets_node(parent, &result.focus_node))
+            .or(parents.first());
+        match parent {
+            Some(parent) => format!("{} {}", self.display(parent), path),
+            None => path,
+        }
+    }
+
+    /// IRI node shapes of the shapes graph with a property shape of path `path`, as displayed.
+    fn parent_shapes(&self, path: &str) -> Vec<SimpleTerm<'a>> {
+        let Some(shapes) = self.shapes else {
+            return Vec::new();
+        };
+        let mut parents: Vec<SimpleTerm> = shapes
+            .triples_matching(Any, [sh::property], Any)
+            .filter_map(Result::ok)
+            .filter(|[parent, _, property_shape]| {
+                parent.is_iri()
+                    && shapes
+                        .triples_matching([*property_shape], [sh::path], Any)
+                        .filter_map(Result::ok)
+                        .any(|t| self.display_path(t[2], shapes) == path)
+            })
+            .map(|[parent, _, _]| parent.clone())
+            .collect();
+        parents.sort_by_key(|parent| self.display(parent));
+        parents.dedup();
+        parents
+    }
+
+    /// Whether `shape` targets the class of `node`, or is itself a class of `node`.
+    fn targets_node(&self, shape: &SimpleTerm, node: &SimpleTerm) -> bool {
+        let Some(shapes) = self.shapes else {
+            return false;
+        };
+        self.data.triples_matching([node], [rdf::type_], Any).filter_map(Result::ok).any(|t| {
+            Term::eq(shape, t[2])
+                || shapes.triples_matching([shape], [sh::targetClass], [t[2]]).next().is_some()
+        })
+    }
+
+    /// Short form of a property path, with complex paths described in `graph` written
+    /// as in SPARQL, e.g. `(cat:hasBatch/^cat:hasChemical)`.
+    fn display_path(&self, path: &SimpleTerm, graph: &LightGraph) -> String {
+        self.display_path_at_depth(path, graph, 0)
+    }
+
+    fn display_path_at_depth(&self, path: &SimpleTerm, graph: &LightGraph, depth: usize) -> String {
+        let object = |predicate: NsTerm| -> Option<SimpleTerm> {
+            graph
+                .triples_matching([path], [predicate], Any)
+                .find_map(|t| t.ok().map(|t| t[2].clone()))
+        };
+        // Paths are short, deeper nesting is a cycle.
+        if !path.is_blank_node() || depth > 16 {
+            return self.display(path);
+        }
+        let display = |step: &SimpleTerm| self.display_path_at_depth(step, graph, depth + 1);
+        if object(rdf::first).is_some() {
+            let steps: Vec<String> = list_members(graph, path).iter().map(display).collect();
+            format!("({})", steps.join("/"))
+        } else if let Some(step) = object(sh::inversePath) {
+            format!("^{}", display(&step))
+        } else if let Some(alternatives) = object(sh::alternativePath) {
+            let steps: Vec<String> =
+                list_members(graph, &alternatives).iter().map(display).collect();
+            format!("({})", steps.join("|"))
+        } else if let Some(step) = object(sh::zeroOrMorePath) {
+            format!("{}*", display(&step))
+        } else if let Some(step) = object(sh::oneOrMorePath) {
+            format!("{}+", display(&step))
+        } else if let Some(step) = object(sh::zeroOrOnePath) {
+            format!("{}?", display(&step))
+        } else {
+            self.display(path)
+        }
+    }
+
+    fn text(&self, report: &ValidationReport) -> String {
+        let mut text = format!(
+            "Validation report: {} ({})\n",
+            if report.conforms { "conforms" } else { "does not conform" },
+            summary_line(report)
+        );
+        for (node_type, results) in self.groups(report) {
+            text.push_str(&format!("\n{}\n", node_type));
+            let mut focus_node = None;
+            for result in results {
+                if focus_node != Some(&result.focus_node) {
+                    focus_node = Some(&result.focus_node);
+                    text.push_str(&format!("  {}\n", self.display(&result.focus_node)));
+                }
+                text.push_str(&format!("    {:<9} ", format!("{:?}", result.severity)));
+                if let Some(path) = &result.result_path {
+                    text.push_str(&format!("{}: ", self.display_path(path, &report.graph)));
+                }
+                text.push_str(&self.message(result));
+                if let Some(value) = &result.value {
+                    text.push_str(&format!(" (value {})", self.display(value)));
+                }
+                text.push('\n');
+            }
+        }
+        text
+    }
+
+    fn json(&self, report: &ValidationReport) -> serde_json::Value {
+        let groups: Vec<serde_json::Value> = self
+            .groups(report)
+            .into_iter()
+            .map(|(node_type, results)| {
+                let results: Vec<serde_json::Value> = results
+                    .into_iter()
+                    .map(|result| {
+                        json!({
+                            "focusNode": self.display(&result.focus_node),
+                            "path": result.result_path.as_ref().map(|p| self.display_path(p, &report.graph)),
+                            "value": result.value.as_ref().map(|v| self.display(v)),
+                            "severity": format!("{:?}", result.severity),
+                            "sourceShape": self.shape_name(result, &report.graph),
+                            "constraintComponent": self.component_name(result),
+                            "message": self.message(result),
+                        })
+                    })
+                    .collect();
+                json!({ "type": node_type, "results": results })
+            })
+            .collect();
+        json!({
+            "conforms": report.conforms,
+            "summary": {
+                "violations": report.count(Severity::Violation),
+                "warnings": report.count(Severity::Warning),
+                "infos": report.count(Severity::Info),
+            },
+            "groups": groups,
+        })
+    }
+
+    fn markdown(&self, report: &ValidationReport) -> String {
+        let mut markdown = format!(
+            "# Validation report\n\n{} **{}** ({})\n",
+            if report.conforms { "✅" } else { "❌" },
+            if report.conforms { "Conforms" } else { "Does not conform" },
+            summary_line(report)
+        );
+        for (node_type, results) in self.groups(report) {
+            markdown.push_str(&format!(
+                "\n## {}\n\n| Focus node | Path | Value | Severity | Message |\n|---|---|---|---|---|\n",
+                markdown_escape(&node_type)
+            ));
+            for result in results {
+                markdown.push_str(&format!(
+                    "| {} | {} | {} | {:?} | {} |\n",
+                    markdown_code(&self.display(&result.focus_node)),
+                    markdown_code(
+                        &result
+                            .result_path
+                            .as_ref()
+                            .map_or_else(String::new, |p| self.display_path(p, &report.graph))
+                    ),
+                    markdown_code(&self.display_option(result.value.as_ref())),
+                    result.severity,
+                    markdown_escape(&self.message(result)),
+                ));
+            }
+        }
+        markdown
+    }
+
+    /// Names of the node shapes of the shapes graph and of their property shapes.
+    fn shape_names(&self) -> BTreeSet<String> {
+        let mut names = BTreeSet::new();
+        let Some(shapes) = self.shapes else {
+            return names;
+        };
+        for triple in shapes.triples_matching(Any, [rdf::type_], [sh::NodeShape]) {
+            let Ok(triple) = triple else { continue };
+            let node_shape: SimpleTerm = triple[0].clone();
+            if !node_shape.is_iri() {
+                continue;
+            }
+            let node_shape_name = self.display(&node_shape);
+            for property in shapes.triples_matching([&node_shape], [sh::property], Any) {
+                let Ok(property) = property else { continue };
+                let property_shape = property[2].clone();
+                if property_shape.is_iri() {
+                    names.insert(self.display(&property_shape));
+                    continue;
+                }
+                let path = shapes
+                    .triples_matching([&property_shape], [sh::path], Any)
+                    .find_map(|t| t.ok().map(|t| self.display_path(t[2], shapes)))
+                    .unwrap_or_else(|| "(no path)".to_string());
+                names.insert(format!("{} {}", node_shape_name, path));
+            }
+            names.insert(node_shape_name);
+        }
+        names
+    }
+
+    fn junit(&self, report: &ValidationReport) -> String {
+        let mut cases: BTreeMap<String, Vec<&ValidationResult>> =
+            self.shape_names().into_iter().map(|name| (name, Vec::new())).collect();
+        for result in &report.results {
+            cases.entry(self.shape_name(result, &report.graph)).or_default().push(result);
+        }
+        let failures = cases
+            .values()
+            .filter(|results| results.iter().any(|r| r.severity >= self.fail_on))
+            .count();
+
+        let mut xml = format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <testsuites name=\"SHACL validation\" tests=\"{tests}\" failures=\"{failures}\">\n  \
+             <testsuite name=\"SHACL validation\" tests=\"{tests}\" failures=\"{failures}\">\n",
+            tests = cases.len(),
+            failures = failures
+        );
+        for (name, results) in cases {
+            xml.push_str(&format!(
+                "    <testcase classname=\"shacl\" name=\"{}\"",
+                xml_escape(&name)
+            ));
+            if results.is_empty() {
+                xml.push_str("/>\n");
+                continue;
+            }
+            xml.push_str(">\n");
+            let details: Vec<String> = results
+                .iter()
+                .map(|result| {
+                    format!(
+                        "{:?} on {} ({}): {}",
+                        result.severity,
+                        self.display(&result.focus_node),
+                        self.node_types(&result.focus_node),
+                        self.message(result)
+                    )
+                })
+                .collect();
+            let details = xml_escape(&details.join("\n"));
+            // The results are sorted, the most severe first.
+            let severity = results[0].severity;
+            if severity >= self.fail_on {
+                xml.push_str(&format!(
+                    "      <failure message=\"{} result{}\" type=\"sh:{:?}\">{}</failure>\n",
+                    results.len(),
+                    if results.len() == 1 { "" } else { "s" },
+                    severity,
+                    details
+                ));
+            } else {
+                // Results below the threshold do not fail the test case.
+                xml.push_str(&format!("      <system-out>{}</system-out>\n", details));
+            }
+            xml.push_str("    </testcase>\n");
+        }
+        xml.push_str("  </testsuite>\n</testsuites>\n");
+        xml
+    }
+}
+
+/// Members of the RDF list at `head`, stopping at malformed or cyclic lists.
+fn list_members<'g>(graph: &'g LightGraph, head: &SimpleTerm) -> Vec<SimpleTerm<'g>> {
+    let mut members = Vec::new();
+    let mut current = head.clone();
+    while members.len() < 64 {
+        let Some(triple) =
+            graph.triples_matching([&current], [rdf::first], Any).find_map(Result::ok)
+        else {
+            break;
+        };
+        members.push(triple[2].clone());
+        let rest = graph
+            .triples_matching([&current], [rdf::rest], Any)
+            .find_map(|t| t.ok().map(|t| t[2].clone()));
+        match rest {
+            Some(rest) if !Term::eq(&rest, rdf::nil) => current = rest,
+            _ => break,
+        }
+    }
+    members
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+fn markdown_escape(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+/// Table cell with `text` as inline code, empty if there is no text.
+fn markdown_code(text: &str) -> String {
+    if text.is_empty() {
+        String::new()
+    } else {
+        format!("`{}`", markdown_escape(text))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use catplus_common::rdf::rdf_parser::parse_turtle_to_graph;
+
+    const DATA: &str = r#"
+        @prefix cat: <http://example.org/cat#> .
+        @prefix ex: <http://example.org/> .
+        ex:campaign a cat:Campaign .
+        ex:batch a cat:Batch .
+    "#;
+
+    const SHAPES: &str = r#"
+        @prefix sh: <http://www.w3.org/ns/shacl#> .
+        @prefix cat: <http://example.org/cat#> .
+        @prefix ex: <http://example.org/> .
+        ex:CampaignShape a sh:NodeShape ;
+            sh:property _:name , _:batch .
+        _:name sh:path cat:name .
+        _:batch sh:path cat:hasBatch .
+        ex:BatchShape a sh:NodeShape .
+    "#;
+
+    const REPORT: &str = r#"
+        @prefix sh: <http://www.w3.org/ns/shacl#> .
+        @prefix cat: <http://example.org/cat#> .
+        @prefix ex: <http://example.org/> .
+        [] a sh:ValidationReport ;
+            sh:conforms false ;
+            sh:result [
+                sh:focusNode ex:campaign ;
+                sh:resultPath cat:name ;
+                sh:resultSeverity sh:Violation ;
+                sh:sourceConstraintComponent sh:MinCountConstraintComponent ;
+                sh:sourceShape ex:CampaignNameShape ;
+                sh:resultMessage "A campaign <needs> a name" ;
+            ] , [
+                sh:focusNode ex:batch ;
+                sh:resultPath [ sh:inversePath cat:hasBatch ] ;
+                sh:resultSeverity sh:Warning ;
+                sh:sourceConstraintComponent sh:MaxCountConstraintComponent ;
+                sh:sourceShape ex:BatchShape ;
+            ] .
+    "#;
+
+    fn render(format: ReportFormat) -> String {
+        render_with(REPORT, format, Severity::Violation)
+    }
+
+    fn render_with(report: &str, format: ReportFormat, fail_on: Severity) -> String {
+        let report = ValidationReport::from_graph(parse_turtle_to_graph(report).unwrap()).unwrap();
+        let data = parse_turtle_to_graph(DATA).unwrap();
+        let shapes = parse_turtle_to_graph(SHAPES).unwrap();
+        render_report(&report, format, fail_on, &data, Some(&shapes)).unwrap()
+    }
+
+    #[test]
+    fn test_text_report() {
+        let text = render(ReportFormat::Text);
+        assert!(text.starts_with(
+            "Validation report: does not conform (1 violation, 1 warning, 0 infos)\n"
+        ));
+        assert!(text.contains("\n<http://example.org/cat#Batch>\n  <http://example.org/batch>\n"));
+        assert!(text.contains("Warning   ^<http://example.org/cat#hasBatch>: MaxCount constraint"));
+        assert!(text.contains("Violation <http://example.org/cat#name>: A campaign <needs> a name"));
+    }
+
+    #[test]
+    fn test_json_report() {
+        let json: serde_json::Value = serde_json::from_str(&render(ReportFormat::Json)).unwrap();
+        assert_eq!(json["conforms"], false);
+        assert_eq!(json["summary"]["violations"], 1);
+        let groups = json["groups"].as_array().unwrap();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[1]["type"], "<http://example.org/cat#Campaign>");
+        assert_eq!(groups[1]["results"][0]["constraintComponent"], "MinCount");
+        assert_eq!(groups[1]["results"][0]["value"], serde_json::Value::Null);
+    }
+
+    #[test]
+    fn test_markdown_report() {
+        let markdown = render(ReportFormat::Markdown);
+        assert!(markdown.contains("## <http://example.org/cat#Campaign>\n"));
+        assert!(markdown.contains(
+            "| `<http://example.org/campaign>` | `<http://example.org/cat#name>` |  | Violation |"
+        ));
+    }
+
+    #[test]
+    fn test_junit_report() {
+        let junit = render(ReportFormat::Junit);
+        // The shapes graph contributes the passing property shape of cat:hasBatch.
+        assert!(junit.contains("tests=\"5\" failures=\"1\""));
+        assert!(junit.contains(
+            "<testcase classname=\"shacl\" name=\"&lt;http://example.org/CampaignShape&gt; \
+             &lt;http://example.org/cat#hasBatch&gt;\"/>"
+        ));
+        assert!(junit.contains("<failure message=\"1 result\" type=\"sh:Violation\">"));
+        assert!(junit.contains("A campaign &lt;needs&gt; a name</failure>"));
+        assert!(junit.contains("<system-out>Warning on &lt;http://example.org/batch&gt;"));
+    }
+
+    #[test]
+    fn test_junit_report_with_blank_node_property_shapes() {
+        // The report of a server has its own blank node labels for the property shapes.
+        let report = REPORT
+            .replace("sh:sourceShape ex:CampaignNameShape", "sh:sourceShape [ sh:path cat:name ]");
+        let junit = render_with(&report, ReportFormat::Junit, Severity::Violation);
+        assert!(junit.contains("tests=\"4\" failures=\"1\""), "{}", junit);
+        assert!(junit.contains(
+            "<testcase classname=\"shacl\" name=\"&lt;http://example.org/CampaignShape&gt; \
+             &lt;http://example.org/cat#name&gt;\">\n      <failure"
+        ));
+    }
+
+    #[test]
+    fn test_junit_report_fail_on_warning() {
+        let junit = render_with(REPORT, ReportFormat::Junit, Severity::Warning);
+        assert!(junit.contains("tests=\"5\" failures=\"2\""));
+        assert!(junit.contains("<failure message=\"1 result\" type=\"sh:Warning\">"));
+        assert!(!junit.contains("<system-out>"));
+    }
+}